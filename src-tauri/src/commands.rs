@@ -1,16 +1,29 @@
+use std::path::Path;
 use tauri::{AppHandle, Manager};
 use tokio::sync::Mutex;
 use tracing::info;
-use crate::config::DynamicConfig;
+use crate::config::{DynamicConfig, UserConfig, USER_CONFIG_DOCUMENT};
+use crate::core::storage::StorageLayer;
+use crate::core::diff::{DiffGranularity, TextDiff};
+use crate::core::explain::PairExplanation;
+use crate::core::files::{self, FileActionReport, FileActionRequest, FileScanResults, ScanOptions, SimilarFileResults};
+use crate::core::masking::{builtin_masks, MaskRule};
+use crate::core::code::CodeClone;
+use crate::core::logs::LogResults;
+use crate::core::records::{Record, RecordResults};
+use crate::core::winnowing::FingerprintMatch;
 use crate::error::{AppError, ErrorCode};
-use crate::state::{DedupManager,DuplicateGroup, DedupStrategySettings,  DedupResults, DedupStats};
+use crate::evaluation::{self, EvaluationReport, GoldStandard, TuningOptions, TuningReport};
+use crate::presets::PresetLibrary;
+use crate::state::{DedupManager, DedupStrategyPreset, DedupStrategySettings, DedupResults, DocumentMetadata};
+use crate::validation::ValidationIssue;
 
 /// Clears all texts from the deduplication manager.
 #[tauri::command]
 pub async fn clear(app_handle: AppHandle) -> Result<(), AppError> {
     let state = app_handle.state::<Mutex<DedupManager>>();
     let mut manager = state.lock().await;
-    manager.clear().map_err(|e| AppError::new(ErrorCode::InternalError, e.to_string()))
+    Ok(manager.clear()?)
 }
 
 #[tauri::command]
@@ -23,8 +36,9 @@ pub async fn add_text(app_handle: AppHandle, text: String) -> Result<usize, AppE
 #[tauri::command]
 pub async fn update_strategy(app_handle: AppHandle, strategy: String) -> Result<String, AppError> {
     info!("🔄 Received strategy update request");
-    let strategy: DedupStrategySettings = serde_json::from_str(&strategy).map_err(|e| AppError::new(ErrorCode::DeserializationError, e.to_string()))?;
+    let strategy: DedupStrategySettings = serde_json::from_str(&strategy)?;
     info!("📥 Incoming strategy data: {:#?}",strategy);
+    strategy.ensure_valid()?;
 
     let dedup_strategy = DedupStrategySettings {
         case_sensitive: strategy.case_sensitive,
@@ -32,6 +46,7 @@ pub async fn update_strategy(app_handle: AppHandle, strategy: String) -> Result<
         ignore_punctuation: strategy.ignore_punctuation,
         normalize_unicode: strategy.normalize_unicode,
         split_strategy: strategy.split_strategy,
+        feature_extraction: strategy.feature_extraction,
         comparison_scope: strategy.comparison_scope,
         min_length: strategy.min_length,
         similarity_threshold: strategy.similarity_threshold,
@@ -41,21 +56,25 @@ pub async fn update_strategy(app_handle: AppHandle, strategy: String) -> Result<
         ignore_stopwords: strategy.ignore_stopwords,
         stemming: strategy.stemming,
         ngram_size: strategy.ngram_size,
+        winnowing_window: strategy.winnowing_window,
         language_detection: strategy.language_detection,
         encoding_normalization: strategy.encoding_normalization,
         adaptive_thresholding: strategy.adaptive_thresholding,
-        config: strategy.config,
+        // The config is edited (and saved) through the config commands; a strategy's copy may be stale
+        config: None,
+        record: strategy.record,
+        blocking: strategy.blocking,
+        domain_normalizers: strategy.domain_normalizers,
+        normalization: strategy.normalization,
+        log: strategy.log,
+        code: strategy.code,
     };
 
     let state = app_handle.state::<Mutex<DedupManager>>();
     let mut manager = state.lock().await;
     
     // Update the strategy and return the updated strategy as JSON
-    let updated_strategy_str = serde_json::to_string(&dedup_strategy)
-        .map_err(|e| AppError::new(
-            ErrorCode::SerializationError,
-            format!("Failed to serialize strategy: {}", e)
-        ))?;
+    let updated_strategy_str = serde_json::to_string(&dedup_strategy)?;
     
     manager.update_strategy(&updated_strategy_str)
         .map_err(|e| AppError::new(
@@ -63,7 +82,15 @@ pub async fn update_strategy(app_handle: AppHandle, strategy: String) -> Result<
             format!("Failed to update strategy: {}", e)
         ))?;
     
-    Ok(updated_strategy_str)
+    // With the active config filled back in
+    Ok(manager.get_strategy())
+}
+
+/// Validates a strategy without applying it, returning every problem found.
+#[tauri::command]
+pub async fn validate_strategy(strategy: String) -> Result<Vec<ValidationIssue>, AppError> {
+    let strategy: DedupStrategySettings = serde_json::from_str(&strategy)?;
+    Ok(strategy.validate())
 }
 
 #[tauri::command]
@@ -78,39 +105,390 @@ pub async fn get_strategy(app_handle: AppHandle) -> Result<String, AppError> {
 pub async fn deduplicate_texts(app_handle: AppHandle) -> Result<DedupResults, AppError> {
     let state = app_handle.state::<Mutex<DedupManager>>();
     let mut manager = state.lock().await;
-    let raw_results = manager.deduplicate_texts().map_err(|e| AppError::new(ErrorCode::InternalError, e.to_string()))?;
-    
-    // Convert the raw results into our frontend-friendly format
-    let mut duplicate_groups = Vec::new();
-    for group in raw_results.duplicate_groups {
-        // Count both original and duplicates (1 + duplicates.len())
-        if group.duplicates.len() >= 1 {
-            let original = group.original;
-            
-            let duplicates = group.duplicates;
-            duplicate_groups.push(DuplicateGroup {
-                original,
-                duplicates,
-                similarity: group.similarity,
-            });
-        }
+    Ok(manager.deduplicate_texts()?)
+}
+
+#[tauri::command]
+pub async fn get_text(app_handle: AppHandle, id: usize) -> Result<String, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    manager.get_text(id).ok_or_else(|| AppError::not_found("Text", id))
+}
+
+/// Adds a record (field name → value) for record deduplication.
+#[tauri::command]
+pub async fn add_record(app_handle: AppHandle, record: Record) -> Result<usize, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let mut manager = state.lock().await;
+    Ok(manager.engine_mut().ingest_record(record))
+}
+
+#[tauri::command]
+pub async fn get_record(app_handle: AppHandle, id: usize) -> Result<Record, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    manager.engine().get_record(id).ok_or_else(|| AppError::not_found("Record", id))
+}
+
+/// Groups duplicate records field by field, using the strategy's `record` settings.
+#[tauri::command]
+pub async fn deduplicate_records(app_handle: AppHandle) -> Result<RecordResults, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    manager.engine().run_records().ok_or_else(|| {
+        AppError::invalid_strategy(
+            "record",
+            "The strategy has no record settings",
+            Some("set record.fields and record.threshold in the strategy".to_string()),
+        )
+    })
+}
+
+/// Mines line templates from the ingested texts (e.g. logs), using the strategy's `log` settings.
+#[tauri::command]
+pub async fn mine_log_templates(app_handle: AppHandle) -> Result<LogResults, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    manager.engine().run_logs().ok_or_else(|| {
+        AppError::invalid_strategy(
+            "log",
+            "The strategy has no log settings",
+            Some("set log to {} to mine templates with the default settings".to_string()),
+        )
+    })
+}
+
+/// Scans a folder tree for byte-identical files (size, then partial hash, then full BLAKE3).
+#[tauri::command]
+pub async fn find_identical_files(options: ScanOptions) -> Result<FileScanResults, AppError> {
+    run_blocking("File scan", move || files::find_identical_files(&options)).await
+}
+
+/// Imports the text files of a folder as whole-text documents and groups the
+/// near-duplicates by path, using the current strategy's method and threshold.
+/// The compared files are added to the session texts, so their ids work with
+/// `explain_pair`, `diff_texts` and `compare_fingerprints`.
+#[tauri::command]
+pub async fn find_similar_files(app_handle: AppHandle, options: ScanOptions) -> Result<SimilarFileResults, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let strategy = state.lock().await.engine().get_strategy().clone();
+    let mut results = run_blocking("Similar file scan", move || files::find_similar_files(&options, &strategy)).await?;
+
+    let mut manager = state.lock().await;
+    results.offset_ids(manager.engine().len());
+    for text in std::mem::take(&mut results.texts) {
+        manager.add_text(text);
     }
-    
-    Ok(DedupResults {
-        duplicate_groups,
-        stats: DedupStats {
-            total_items: raw_results.stats.total_items,
-            unique_items: raw_results.stats.unique_items,
-            duplicate_groups: raw_results.stats.duplicate_groups,
-        }
+    Ok(results)
+}
+
+/// Deletes, hardlinks or quarantines the duplicates of scanned groups, keeping the
+/// first path of each. Needs `confirmed` unless it is a `dry_run`.
+#[tauri::command]
+pub async fn apply_file_action(request: FileActionRequest) -> Result<FileActionReport, AppError> {
+    info!("File action {:?} on {} groups (dry run: {})", request.action, request.groups.len(), request.dry_run);
+    run_blocking("File action", move || files::apply_action(&request)).await
+}
+
+/// Returns the metadata recorded for a text, such as its detected language.
+#[tauri::command]
+pub async fn get_document_metadata(app_handle: AppHandle, id: usize) -> Result<DocumentMetadata, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    manager.engine().get_metadata(id).ok_or_else(|| AppError::not_found("Text", id))
+}
+
+/// Compares the winnowed fingerprints of two texts: containment in both
+/// directions and the positions of the shared fingerprints.
+#[tauri::command]
+pub async fn compare_fingerprints(app_handle: AppHandle, id_a: usize, id_b: usize) -> Result<FingerprintMatch, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    manager.engine().fingerprint_match(id_a, id_b).ok_or_else(|| {
+        let missing = if manager.get_text(id_a).is_none() { id_a } else { id_b };
+        AppError::not_found("Text", missing)
     })
 }
 
+/// Compares two texts as source code (see the strategy's `code` settings):
+/// token containment in both directions and the line ranges of the clone.
 #[tauri::command]
-pub async fn get_text(app_handle: AppHandle, id: usize) -> Result<String, AppError> {
+pub async fn find_code_clone(app_handle: AppHandle, id_a: usize, id_b: usize) -> Result<CodeClone, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    manager.engine().code_clone(id_a, id_b).ok_or_else(|| {
+        let missing = if manager.get_text(id_a).is_none() { id_a } else { id_b };
+        AppError::not_found("Text", missing)
+    })
+}
+
+/// Explains why two texts are or are not grouped: each normalization step's
+/// output, the features, shared and unique features, scores and the threshold decision.
+#[tauri::command]
+pub async fn explain_pair(app_handle: AppHandle, id_a: usize, id_b: usize) -> Result<PairExplanation, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    manager.engine().explain_pair(id_a, id_b).ok_or_else(|| {
+        let missing = if manager.get_text(id_a).is_none() { id_a } else { id_b };
+        AppError::not_found("Text", missing)
+    })
+}
+
+/// Diffs two texts, typically a group's `original_id` and one of its members,
+/// at word (default) or character granularity.
+#[tauri::command]
+pub async fn diff_texts(
+    app_handle: AppHandle,
+    id_a: usize,
+    id_b: usize,
+    granularity: Option<DiffGranularity>,
+) -> Result<TextDiff, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    diff_pair(&manager, id_a, id_b, granularity)
+}
+
+/// Diffs two texts like `diff_texts` and renders the result as inline HTML
+/// (`<del>`/`<ins>`, with `class="moved"` on moved blocks).
+#[tauri::command]
+pub async fn render_diff_html(
+    app_handle: AppHandle,
+    id_a: usize,
+    id_b: usize,
+    granularity: Option<DiffGranularity>,
+) -> Result<String, AppError> {
     let state = app_handle.state::<Mutex<DedupManager>>();
     let manager = state.lock().await;
-    manager.get_text(id).ok_or_else(|| AppError::new(ErrorCode::InternalError, "Text not found".to_string()))
+    diff_pair(&manager, id_a, id_b, granularity).map(|diff| diff.to_html())
+}
+
+fn diff_pair(
+    manager: &DedupManager,
+    id_a: usize,
+    id_b: usize,
+    granularity: Option<DiffGranularity>,
+) -> Result<TextDiff, AppError> {
+    manager.engine().diff(id_a, id_b, granularity.unwrap_or_default()).ok_or_else(|| {
+        let missing = if manager.get_text(id_a).is_none() { id_a } else { id_b };
+        AppError::not_found("Text", missing)
+    })
+}
+
+/// Runs blocking work (file reads, hashing, sweeps) off the async runtime,
+/// reporting a task cancelled by runtime shutdown as `Cancelled`.
+async fn run_blocking<T: Send + 'static>(
+    operation: &str,
+    work: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    match tauri::async_runtime::spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(tauri::Error::JoinError(e)) if e.is_cancelled() => Err(AppError::cancelled(operation)),
+        Err(e) => Err(AppError::new(ErrorCode::InternalError, format!("{} failed: {}", operation, e))),
+    }
+}
+
+/// Scores the current strategy against a gold file of labeled duplicates.
+/// Runs on the file's own texts; the ingested texts are untouched.
+#[tauri::command]
+pub async fn evaluate_strategy(app_handle: AppHandle, path: String) -> Result<EvaluationReport, AppError> {
+    let strategy = {
+        let state = app_handle.state::<Mutex<DedupManager>>();
+        let manager = state.lock().await;
+        manager.engine().get_strategy().clone()
+    };
+    run_blocking("Evaluation", move || {
+        let gold = GoldStandard::load(Path::new(&path))?;
+        evaluation::evaluate(&strategy, &gold)
+    })
+    .await
+}
+
+/// Sweeps thresholds, n-gram sizes and similarity methods against a gold file,
+/// returning precision/recall curves and the best strategy. With `save_as` set,
+/// the best strategy is also saved as a user preset.
+#[tauri::command]
+pub async fn tune_strategy(
+    app_handle: AppHandle,
+    path: String,
+    options: Option<TuningOptions>,
+) -> Result<TuningReport, AppError> {
+    let options = options.unwrap_or_default();
+    let base = {
+        let state = app_handle.state::<Mutex<DedupManager>>();
+        let manager = state.lock().await;
+        manager.engine().get_strategy().clone()
+    };
+    let (gold_path, sweep) = (path.clone(), options.clone());
+    let mut report = run_blocking("Tuning", move || {
+        let gold = GoldStandard::load(Path::new(&gold_path))?;
+        evaluation::tune(&base, &gold, &sweep)
+    })
+    .await?;
+
+    if let Some(name) = options.save_as {
+        let storage = storage(&app_handle)?;
+        let mut library = PresetLibrary::load(&storage)?;
+        library.add(DedupStrategyPreset {
+            name: name.clone(),
+            description: format!(
+                "Tuned on {} (pairwise F1 {:.2})",
+                path,
+                report.best_report.pairwise.f1
+            ),
+            settings: report.best.clone(),
+            builtin: false,
+        })?;
+        library.save(&storage)?;
+        report.saved_preset = Some(name);
+    }
+    Ok(report)
+}
+
+/// Resolves the storage rooted at the app config directory.
+fn storage(app_handle: &AppHandle) -> Result<StorageLayer, AppError> {
+    let dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| AppError::storage(None, "resolve config directory", e.to_string()))?;
+    Ok(StorageLayer::new(dir))
+}
+
+/// Lists built-in and user presets.
+#[tauri::command]
+pub async fn list_presets(app_handle: AppHandle) -> Result<Vec<DedupStrategyPreset>, AppError> {
+    let library = PresetLibrary::load(&storage(&app_handle)?)?;
+    Ok(library.list())
+}
+
+/// Applies a preset's settings as the current strategy and returns it as JSON.
+/// A config carried by the preset is persisted as the user config.
+#[tauri::command]
+pub async fn apply_preset(app_handle: AppHandle, name: String) -> Result<String, AppError> {
+    let library = PresetLibrary::load(&storage(&app_handle)?)?;
+    let preset = library.get(&name).ok_or_else(|| AppError::not_found("Preset", &name))?;
+    preset.settings.ensure_valid()?;
+    info!("🎛️ Applying preset '{}'", name);
+
+    // A preset's config replaces the active one; save it like any other config edit
+    if let Some(config) = preset.settings.config.clone() {
+        edit_config(&app_handle, |current| {
+            *current = config;
+            Ok(())
+        })
+        .await?;
+    }
+
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let mut manager = state.lock().await;
+    manager.engine_mut().update_strategy(preset.settings)?;
+    Ok(manager.get_strategy())
+}
+
+/// Saves a new user preset given as JSON.
+#[tauri::command]
+pub async fn save_preset(app_handle: AppHandle, preset: String) -> Result<(), AppError> {
+    let preset: DedupStrategyPreset = serde_json::from_str(&preset)?;
+    let storage = storage(&app_handle)?;
+    let mut library = PresetLibrary::load(&storage)?;
+    library.add(preset)?;
+    library.save(&storage)
+}
+
+/// Replaces the user preset called `name` with the given JSON preset.
+#[tauri::command]
+pub async fn update_preset(app_handle: AppHandle, name: String, preset: String) -> Result<(), AppError> {
+    let preset: DedupStrategyPreset = serde_json::from_str(&preset)?;
+    let storage = storage(&app_handle)?;
+    let mut library = PresetLibrary::load(&storage)?;
+    library.update(&name, preset)?;
+    library.save(&storage)
+}
+
+/// Deletes the user preset called `name`.
+#[tauri::command]
+pub async fn delete_preset(app_handle: AppHandle, name: String) -> Result<(), AppError> {
+    let storage = storage(&app_handle)?;
+    let mut library = PresetLibrary::load(&storage)?;
+    library.remove(&name)?;
+    library.save(&storage)
+}
+
+/// Imports presets from a JSON file and returns the imported names.
+#[tauri::command]
+pub async fn import_presets(app_handle: AppHandle, path: String) -> Result<Vec<String>, AppError> {
+    let storage = storage(&app_handle)?;
+    let mut library = PresetLibrary::load(&storage)?;
+    let imported = library.import(Path::new(&path))?;
+    library.save(&storage)?;
+    Ok(imported)
+}
+
+/// Exports the named presets (or all user presets) to a JSON file.
+#[tauri::command]
+pub async fn export_presets(app_handle: AppHandle, path: String, names: Option<Vec<String>>) -> Result<usize, AppError> {
+    let library = PresetLibrary::load(&storage(&app_handle)?)?;
+    library.export(Path::new(&path), names.as_deref())
+}
+
+/// Applies an edit to the active dynamic config and persists the user overrides.
+async fn edit_config(
+    app_handle: &AppHandle,
+    edit: impl FnOnce(&mut DynamicConfig) -> Result<(), String>,
+) -> Result<UserConfig, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let mut manager = state.lock().await;
+
+    let mut config = manager.engine().get_config().clone();
+    edit(&mut config).map_err(|e| AppError::new(ErrorCode::InvalidInput, e))?;
+    let user_config = config.user_config();
+
+    storage(app_handle)?.save(USER_CONFIG_DOCUMENT, &user_config)?;
+    manager.engine_mut().update_config(config);
+    Ok(user_config)
+}
+
+/// Returns the user-defined stop words and delimiters.
+#[tauri::command]
+pub async fn get_config(app_handle: AppHandle) -> Result<UserConfig, AppError> {
+    let state = app_handle.state::<Mutex<DedupManager>>();
+    let manager = state.lock().await;
+    Ok(manager.engine().get_config().user_config())
+}
+
+/// Adds user stop words for a language (ISO 639-1 code, e.g. "en").
+#[tauri::command]
+pub async fn add_stop_words(app_handle: AppHandle, language: String, words: Vec<String>) -> Result<UserConfig, AppError> {
+    edit_config(&app_handle, |config| config.add_user_stop_words(&language, &words)).await
+}
+
+/// Removes user stop words for a language.
+#[tauri::command]
+pub async fn remove_stop_words(app_handle: AppHandle, language: String, words: Vec<String>) -> Result<UserConfig, AppError> {
+    edit_config(&app_handle, |config| config.remove_user_stop_words(&language, &words)).await
+}
+
+/// Replaces the custom sentence delimiters.
+#[tauri::command]
+pub async fn set_sentence_delimiters(app_handle: AppHandle, delimiters: Vec<char>) -> Result<UserConfig, AppError> {
+    edit_config(&app_handle, |config| config.set_user_sentence_delimiters(delimiters)).await
+}
+
+/// Sets the paragraph delimiter; `None` restores the default.
+#[tauri::command]
+pub async fn set_paragraph_delimiter(app_handle: AppHandle, delimiter: Option<String>) -> Result<UserConfig, AppError> {
+    edit_config(&app_handle, |config| config.set_user_paragraph_delimiters(delimiter)).await
+}
+
+/// Replaces the masking rules (regex → placeholder), applied in order before comparison.
+#[tauri::command]
+pub async fn set_masking_rules(app_handle: AppHandle, rules: Vec<MaskRule>) -> Result<UserConfig, AppError> {
+    edit_config(&app_handle, |config| config.set_user_masking_rules(rules)).await
+}
+
+/// Returns the built-in masks (UUID, TIMESTAMP, IP, HEX, NUM) to pick rules from.
+#[tauri::command]
+pub async fn get_builtin_masks() -> Result<Vec<MaskRule>, AppError> {
+    Ok(builtin_masks())
 }
 
 #[cfg(test)]
@@ -236,4 +614,156 @@ mod tests {
         let strategy = guard.get_strategy();
         assert!(strategy.contains("Exact"), "Strategy should be updated to Exact");
     }
+
+    #[tokio::test]
+    async fn test_validate_strategy() {
+        use crate::state::{FuzzyAlgorithm, SimilarityMethod};
+
+        // Default strategy is valid
+        assert!(DedupStrategySettings::default().validate().is_empty());
+
+        // Every problem is reported with its field path
+        let strategy = DedupStrategySettings {
+            similarity_threshold: 1.5,
+            ngram_size: None,
+            similarity_method: SimilarityMethod::Fuzzy(FuzzyAlgorithm::NGram),
+            ..DedupStrategySettings::default()
+        };
+        let issues = validate_strategy(serde_json::to_string(&strategy).unwrap()).await.unwrap();
+        let fields: Vec<&str> = issues.iter().map(|issue| issue.field.as_str()).collect();
+        assert_eq!(fields, vec!["similarity_threshold", "ngram_size"]);
+        assert!(issues.iter().all(|issue| issue.suggestion.is_some()));
+
+        // Invalid strategies are rejected before being applied
+        let err = strategy.ensure_valid().unwrap_err();
+        assert!(matches!(err.code, ErrorCode::InvalidStrategy));
+
+        // Masking rules in the strategy's config must compile
+        let config = crate::config::DynamicConfig {
+            user_masking_rules: vec![serde_json::from_value(serde_json::json!({
+                "name": "broken", "pattern": "(unclosed", "replacement": "<X>"
+            }))
+            .unwrap()],
+            ..Default::default()
+        };
+        let masked = DedupStrategySettings { config: Some(config), ..DedupStrategySettings::default() };
+        let fields: Vec<String> = masked.validate().into_iter().map(|issue| issue.field).collect();
+        assert_eq!(fields, vec!["config.user_masking_rules[0].pattern".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_word_shingles_respect_word_order() {
+        use crate::state::FeatureExtraction;
+
+        let manager = setup();
+        let mut guard = manager.lock().await;
+        guard.add_text("dog bites man".to_string());
+        guard.add_text("man bites dog".to_string());
+
+        // A bag of words ignores order
+        let results = guard.deduplicate_texts().unwrap();
+        assert_eq!(results.duplicate_groups.len(), 1);
+
+        // Word 2-shingles keep it
+        let strategy = DedupStrategySettings {
+            feature_extraction: Some(FeatureExtraction::WordShingles(2)),
+            ..DedupStrategySettings::default()
+        };
+        guard.update_strategy(&serde_json::to_string(&strategy).unwrap()).unwrap();
+        let results = guard.deduplicate_texts().unwrap();
+        assert!(results.duplicate_groups.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_report_matching_spans() {
+        use crate::state::SplitStrategy;
+
+        let manager = setup();
+        let mut guard = manager.lock().await;
+        let strategy = DedupStrategySettings {
+            split_strategy: SplitStrategy::Sentences,
+            ignore_punctuation: Some(false),
+            ..DedupStrategySettings::default()
+        };
+        guard.update_strategy(&serde_json::to_string(&strategy).unwrap()).unwrap();
+
+        let original = "Prices rose sharply in March. Analysts were surprised.";
+        let duplicate = "Café owners complained. Prices rose sharply in March. Analysts were surprised.";
+        guard.add_text(original.to_string());
+        guard.add_text(duplicate.to_string());
+
+        let results = guard.deduplicate_texts().unwrap();
+        let group = &results.duplicate_groups[0];
+        assert_eq!(group.original_id, 0);
+
+        let member = &group.members[0];
+        assert_eq!(member.id, 1);
+        assert_eq!(member.matches.len(), 2);
+        let first = member.matches[0];
+        assert_eq!(&original[first.original.start..first.original.end], "Prices rose sharply in March.");
+        assert_eq!(&duplicate[first.duplicate.start..first.duplicate.end], "Prices rose sharply in March.");
+        // "é" is two bytes but one char
+        assert_eq!(first.duplicate.char_start, first.duplicate.start - 1);
+        assert_eq!(member.span.end, duplicate.len());
+    }
+
+    #[tokio::test]
+    async fn test_explain_pair() {
+        use crate::core::explain::NormalizationStepKind;
+
+        let manager = setup();
+        let mut guard = manager.lock().await;
+        guard.add_text("The Cats are running!".to_string());
+        guard.add_text("the cat runs".to_string());
+
+        let explanation = guard.engine().explain_pair(0, 1).unwrap();
+        let steps: Vec<NormalizationStepKind> = explanation.a.steps.iter().map(|step| step.step).collect();
+        assert_eq!(steps.len(), 9);
+        assert!(!explanation.a.steps[0].applied, "no masking rules are configured");
+        assert!(!explanation.a.steps[1].applied, "no domain normalizers are selected");
+        assert_eq!(steps[2], NormalizationStepKind::Case);
+        assert_eq!(explanation.a.steps[2].output, "the cats are running!");
+        assert_eq!(explanation.a.normalized, explanation.a.steps[8].output);
+
+        // Stemming brings both texts to the same features
+        assert_eq!(explanation.shared_features, vec!["cat".to_string(), "run".to_string()]);
+        assert!(explanation.unique_a.is_empty() && explanation.unique_b.is_empty());
+        assert_eq!(explanation.raw_score, 1.0);
+        assert!(explanation.is_duplicate);
+        assert!(guard.engine().explain_pair(0, 7).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_masking_rules_in_explain() {
+        let manager = setup();
+        let mut guard = manager.lock().await;
+        let mut config = guard.engine().get_config().clone();
+        config.set_user_masking_rules(builtin_masks()).unwrap();
+        guard.engine_mut().update_config(config);
+
+        guard.add_text("Request 3f2a9c1e-0b4d-4c6e-9f8a-7b6c5d4e3f2a timed out after 30 seconds".to_string());
+        guard.add_text("Request 9c1e3f2a-4c6e-0b4d-7b6c-9f8a5d4e3f2a timed out after 45 seconds".to_string());
+
+        let explanation = guard.engine().explain_pair(0, 1).unwrap();
+        assert!(explanation.a.steps[0].applied);
+        assert_eq!(explanation.a.masks.len(), 2, "UUID and NUM fired");
+        assert_eq!(explanation.a.normalized, explanation.b.normalized);
+        assert!(explanation.is_duplicate);
+    }
+
+    #[tokio::test]
+    async fn test_diff_rendered_as_html() {
+        let manager = setup();
+        let mut guard = manager.lock().await;
+        guard.add_text("if a < b then stop".to_string());
+        guard.add_text("if a < c then stop".to_string());
+
+        let html = diff_pair(&guard, 0, 1, None).unwrap().to_html();
+        assert!(html.starts_with("<div class=\"diff\">"));
+        assert!(html.contains("<del>b</del><ins>c</ins>"));
+        assert!(html.contains("a &lt; "), "text is escaped");
+
+        let err = diff_pair(&guard, 0, 5, Some(DiffGranularity::Char)).unwrap_err();
+        assert!(matches!(err.code, ErrorCode::NotFound));
+    }
 }
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use stop_words::{get, LANGUAGE as StopLanguage};
-use whatlang::{detect, Lang};
+use stop_words::get;
+use crate::core::language::{detect_language, DEFAULT_LANGUAGE, LANGUAGES};
+use crate::core::masking::MaskRule;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeduplicationConfig {
@@ -18,29 +19,14 @@ impl Default for DeduplicationConfig {
     fn default() -> Self {
         let mut stop_words = HashMap::new();
         
-        // Supported languages mapped to ISO codes
-        let supported_langs = [
-            (StopLanguage::English, "en"),
-            (StopLanguage::French, "fr"),
-            (StopLanguage::Spanish, "es"),
-            (StopLanguage::German, "de"),
-            (StopLanguage::Italian, "it"),
-            (StopLanguage::Portuguese, "pt"),
-            (StopLanguage::Russian, "ru"),
-            (StopLanguage::Japanese, "ja"),
-            (StopLanguage::Chinese, "zh"),
-            (StopLanguage::Arabic, "ar"),
-            (StopLanguage::Hindi, "hi"),
-            (StopLanguage::Korean, "ko"),
-            (StopLanguage::Dutch, "nl"),
-            (StopLanguage::Swedish, "sv"),
-            (StopLanguage::Turkish, "tr"),
-        ];
-
-        // Load stop words for supported languages
-        for (lang, code) in &supported_langs {
-            let words_set: HashSet<String> = get(lang.clone()).into_iter().collect();
-            stop_words.insert(code.to_string(), words_set);
+        // Load stop words for every language that has a list
+        let mut supported_languages = HashSet::new();
+        for support in LANGUAGES {
+            if let Some(lang) = &support.stop_words {
+                let words_set: HashSet<String> = get(lang.clone()).into_iter().collect();
+                stop_words.insert(support.code.to_string(), words_set);
+                supported_languages.insert(support.code.to_string());
+            }
         }
 
         Self {
@@ -52,7 +38,7 @@ impl Default for DeduplicationConfig {
             default_ngram_size: 3,
             default_min_length: 10,
             default_similarity_threshold: 0.8,
-            supported_languages: supported_langs.iter().map(|(_, code)| code.to_string()).collect(),
+            supported_languages,
         }
     }
 }
@@ -68,8 +54,23 @@ pub struct DynamicConfig {
     pub user_stop_words: HashMap<String, HashSet<String>>, // Custom user-defined stop words
     pub user_sentence_delimiters: HashSet<char>,           // Additional user-defined sentence delimiters
     pub user_paragraph_delimiters: Option<String>,         // Custom paragraph delimiter
+    #[serde(default)]
+    pub user_masking_rules: Vec<MaskRule>, // Regex masks applied, in order, before comparison
 }
 
+/// **User overrides of the dynamic config, persisted between sessions.**
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserConfig {
+    pub stop_words: HashMap<String, HashSet<String>>,
+    pub sentence_delimiters: HashSet<char>,
+    pub paragraph_delimiter: Option<String>,
+    #[serde(default)]
+    pub masking_rules: Vec<MaskRule>,
+}
+
+/// Storage document holding the [`UserConfig`]
+pub const USER_CONFIG_DOCUMENT: &str = "config";
+
 impl Default for DynamicConfig {
     fn default() -> Self {
         Self {
@@ -77,11 +78,100 @@ impl Default for DynamicConfig {
             user_stop_words: HashMap::new(),
             user_sentence_delimiters: HashSet::new(),
             user_paragraph_delimiters: None,
+            user_masking_rules: Vec::new(),
         }
     }
 }
 
 impl DynamicConfig {
+    /// **Rebuilds a config from persisted user overrides.**
+    pub fn from_user_config(user: UserConfig) -> Self {
+        Self {
+            base: DeduplicationConfig::default(),
+            user_stop_words: user.stop_words,
+            user_sentence_delimiters: user.sentence_delimiters,
+            user_paragraph_delimiters: user.paragraph_delimiter,
+            user_masking_rules: user.masking_rules,
+        }
+    }
+
+    /// **Returns the user-defined part of the config, as persisted.**
+    pub fn user_config(&self) -> UserConfig {
+        UserConfig {
+            stop_words: self.user_stop_words.clone(),
+            sentence_delimiters: self.user_sentence_delimiters.clone(),
+            paragraph_delimiter: self.user_paragraph_delimiters.clone(),
+            masking_rules: self.user_masking_rules.clone(),
+        }
+    }
+
+    /// **Adds user stop words for a supported language.**
+    pub fn add_user_stop_words(&mut self, lang_code: &str, words: &[String]) -> Result<(), String> {
+        let lang_code = self.check_language(lang_code)?;
+        let entry = self.user_stop_words.entry(lang_code).or_default();
+        entry.extend(
+            words
+                .iter()
+                .map(|word| word.trim().to_lowercase())
+                .filter(|word| !word.is_empty()),
+        );
+        Ok(())
+    }
+
+    /// **Removes user stop words for a language (built-in stop words are kept).**
+    pub fn remove_user_stop_words(&mut self, lang_code: &str, words: &[String]) -> Result<(), String> {
+        let lang_code = self.check_language(lang_code)?;
+        if let Some(entry) = self.user_stop_words.get_mut(&lang_code) {
+            for word in words {
+                entry.remove(&word.trim().to_lowercase());
+            }
+            if entry.is_empty() {
+                self.user_stop_words.remove(&lang_code);
+            }
+        }
+        Ok(())
+    }
+
+    /// **Replaces the custom sentence delimiters.**
+    pub fn set_user_sentence_delimiters(&mut self, delimiters: impl IntoIterator<Item = char>) -> Result<(), String> {
+        let delimiters: HashSet<char> = delimiters.into_iter().collect();
+        if let Some(c) = delimiters.iter().find(|c| c.is_whitespace()) {
+            return Err(format!("Whitespace {:?} cannot be a sentence delimiter", c));
+        }
+        self.user_sentence_delimiters = delimiters;
+        Ok(())
+    }
+
+    /// **Sets the custom paragraph delimiter (`None` restores the default).**
+    pub fn set_user_paragraph_delimiters(&mut self, delimiter: Option<String>) -> Result<(), String> {
+        if delimiter.as_deref() == Some("") {
+            return Err("Paragraph delimiter must not be empty".to_string());
+        }
+        self.user_paragraph_delimiters = delimiter;
+        Ok(())
+    }
+
+    /// **Replaces the masking rules, rejecting invalid or unnamed ones.**
+    pub fn set_user_masking_rules(&mut self, rules: Vec<MaskRule>) -> Result<(), String> {
+        for rule in &rules {
+            if rule.name.trim().is_empty() {
+                return Err("Masking rules need a name".to_string());
+            }
+            rule.compile()?;
+        }
+        self.user_masking_rules = rules;
+        Ok(())
+    }
+
+    fn check_language(&self, lang_code: &str) -> Result<String, String> {
+        let lang_code = lang_code.trim().to_lowercase();
+        if self.base.supported_languages.contains(&lang_code) {
+            Ok(lang_code)
+        } else {
+            Err(format!("Unsupported language code: {}", lang_code))
+        }
+    }
+
     /// **Merges system-defined and user-defined stop words.**
     pub fn merge_stop_words(&self, lang_code: &str) -> HashSet<String> {
         let base_words = self.base.stop_words.get(lang_code).cloned().unwrap_or_default();
@@ -91,27 +181,13 @@ impl DynamicConfig {
 
     /// **Returns merged stop words for detected language.**
     pub fn get_stop_words_for_text(&self, text: &str) -> HashSet<String> {
-        // Detect language of the text
-        let lang_code = detect(text)
-            .map(|info| match info.lang() {
-                Lang::Eng => "en",
-                Lang::Fra => "fr",
-                Lang::Spa => "es",
-                Lang::Deu => "de",
-                Lang::Ita => "it",
-                Lang::Por => "pt",
-                Lang::Rus => "ru",
-                Lang::Jpn => "ja",
-                Lang::Cmn => "zh",
-                Lang::Ara => "ar",
-                Lang::Hin => "hi",
-                Lang::Kor => "ko",
-                Lang::Nld => "nl",
-                Lang::Swe => "sv",
-                Lang::Tur => "tr",
-                _ => "en", // Default to English if unsupported
-            })
-            .unwrap_or("en");
+        // Detect language of the text, defaulting to English if unsupported
+        let detected = detect_language(text);
+        let lang_code = detected
+            .as_ref()
+            .map(|info| info.code.as_str())
+            .filter(|code| self.base.stop_words.contains_key(*code))
+            .unwrap_or(DEFAULT_LANGUAGE);
 
         self.merge_stop_words(lang_code)
     }
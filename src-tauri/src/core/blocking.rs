@@ -0,0 +1,371 @@
+//! Blocking: cheap candidate generation in front of the pairwise comparison loop.
+//!
+//! Key rules (prefix, phonetic, key expression) only pair texts with the same key;
+//! distance rules (sorted neighbourhood, length band) only pair texts that are
+//! close. With several rules a pair has to pass all of them.
+
+use std::collections::HashMap;
+
+use rphonetic::{Encoder, Soundex};
+use serde::{Deserialize, Serialize};
+use symbolic_expressions::parser::parse_str;
+use symbolic_expressions::Sexp;
+
+/// One blocking rule, stored in `DedupStrategySettings::blocking`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BlockingRule {
+    /// Same first `length` alphanumeric characters
+    Prefix { length: usize },
+    /// Fewer than `window` positions apart once the texts are sorted
+    SortedNeighbourhood { window: usize },
+    /// Same Soundex codes for the first `words` words
+    Phonetic { words: usize },
+    /// Char lengths differing by at most `tolerance` × the longer length
+    LengthBand { tolerance: f64 },
+    /// Same value of a key expression, e.g. `(concat (prefix 2 (word 0)) (length-bucket 50))`
+    Key { expression: String },
+}
+
+/// Comparison counts of one clustering run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockingStats {
+    pub comparisons: usize,        // Pairs actually scored
+    pub pruned_comparisons: usize, // Pairs ruled out by blocking
+}
+
+// ---------------------------------------------------------------------
+// Key expressions
+// ---------------------------------------------------------------------
+
+/// Parsed key expression. Expressions are S-expressions over the blocking
+/// text (lowercase words of alphanumerics):
+///
+/// - `text` — the whole blocking text
+/// - `(word N)` — Nth word, counting from the end when negative
+/// - `(prefix N e)`, `(suffix N e)` — first / last N chars of `e`
+/// - `(soundex e)` — Soundex code of every word of `e`
+/// - `(initials e)` — first char of every word of `e`
+/// - `(sorted e)` — words of `e` in sorted order
+/// - `(length-bucket N)` — char length of the text divided by N
+/// - `(concat e...)` — values joined with `|`
+///
+/// Any other atom is a literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyExpression {
+    Text,
+    Literal(String),
+    Word(isize),
+    Prefix(usize, Box<KeyExpression>),
+    Suffix(usize, Box<KeyExpression>),
+    Soundex(Box<KeyExpression>),
+    Initials(Box<KeyExpression>),
+    Sorted(Box<KeyExpression>),
+    LengthBucket(usize),
+    Concat(Vec<KeyExpression>),
+}
+
+impl KeyExpression {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let sexp = parse_str(expression.trim()).map_err(|e| format!("{}", e))?;
+        Self::from_sexp(&sexp)
+    }
+
+    fn from_sexp(sexp: &Sexp) -> Result<Self, String> {
+        let items = match sexp {
+            Sexp::String(atom) if atom == "text" => return Ok(KeyExpression::Text),
+            Sexp::String(atom) => return Ok(KeyExpression::Literal(atom.clone())),
+            Sexp::Empty => return Err("empty key expression".to_string()),
+            Sexp::List(items) => items,
+        };
+
+        let (name, args) = match items.split_first() {
+            Some((Sexp::String(name), args)) => (name.as_str(), args),
+            _ => return Err("a list must start with a function name".to_string()),
+        };
+        let number = |idx: usize| -> Result<isize, String> {
+            match args.get(idx) {
+                Some(Sexp::String(n)) => n.parse().map_err(|_| format!("{}: '{}' is not a number", name, n)),
+                _ => Err(format!("{}: argument {} must be a number", name, idx + 1)),
+            }
+        };
+        let count = |idx: usize| -> Result<usize, String> {
+            usize::try_from(number(idx)?).map_err(|_| format!("{}: argument {} must not be negative", name, idx + 1))
+        };
+        let operand = |idx: usize| -> Result<Box<KeyExpression>, String> {
+            match args.get(idx) {
+                Some(arg) => Ok(Box::new(Self::from_sexp(arg)?)),
+                None => Err(format!("{}: missing argument {}", name, idx + 1)),
+            }
+        };
+        let arity = |expected: usize| -> Result<(), String> {
+            if args.len() == expected {
+                Ok(())
+            } else {
+                Err(format!("{} takes {} argument(s), got {}", name, expected, args.len()))
+            }
+        };
+
+        match name {
+            "word" => arity(1).and_then(|_| Ok(KeyExpression::Word(number(0)?))),
+            "prefix" => arity(2).and_then(|_| Ok(KeyExpression::Prefix(count(0)?, operand(1)?))),
+            "suffix" => arity(2).and_then(|_| Ok(KeyExpression::Suffix(count(0)?, operand(1)?))),
+            "soundex" => arity(1).and_then(|_| Ok(KeyExpression::Soundex(operand(0)?))),
+            "initials" => arity(1).and_then(|_| Ok(KeyExpression::Initials(operand(0)?))),
+            "sorted" => arity(1).and_then(|_| Ok(KeyExpression::Sorted(operand(0)?))),
+            "length-bucket" => match count(0)? {
+                0 => Err("length-bucket: bucket width must be at least 1".to_string()),
+                width => arity(1).map(|_| KeyExpression::LengthBucket(width)),
+            },
+            "concat" => args.iter().map(Self::from_sexp).collect::<Result<_, _>>().map(KeyExpression::Concat),
+            other => Err(format!("unknown key function '{}'", other)),
+        }
+    }
+
+    /// Evaluates the expression on a blocking text
+    pub fn eval(&self, text: &str) -> String {
+        match self {
+            KeyExpression::Text => text.to_string(),
+            KeyExpression::Literal(value) => value.clone(),
+            KeyExpression::Word(n) => {
+                let words: Vec<&str> = text.split(' ').filter(|w| !w.is_empty()).collect();
+                let idx = if *n < 0 { words.len() as isize + n } else { *n };
+                usize::try_from(idx).ok().and_then(|idx| words.get(idx)).unwrap_or(&"").to_string()
+            }
+            KeyExpression::Prefix(n, inner) => inner.eval(text).chars().take(*n).collect(),
+            KeyExpression::Suffix(n, inner) => {
+                let value: Vec<char> = inner.eval(text).chars().collect();
+                value[value.len().saturating_sub(*n)..].iter().collect()
+            }
+            KeyExpression::Soundex(inner) => soundex_words(&inner.eval(text), usize::MAX),
+            KeyExpression::Initials(inner) => {
+                inner.eval(text).split_whitespace().filter_map(|word| word.chars().next()).collect()
+            }
+            KeyExpression::Sorted(inner) => {
+                let value = inner.eval(text);
+                let mut words: Vec<&str> = value.split_whitespace().collect();
+                words.sort_unstable();
+                words.join(" ")
+            }
+            KeyExpression::LengthBucket(width) => (text.chars().count() / width).to_string(),
+            KeyExpression::Concat(parts) => parts.iter().map(|part| part.eval(text)).collect::<Vec<_>>().join("|"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------
+// Blocker
+// ---------------------------------------------------------------------
+
+/// A rule evaluated over every text
+enum RuleIndex {
+    Key(Vec<String>),
+    Rank { order: Vec<usize>, rank: Vec<usize>, window: usize },
+    Length { lengths: Vec<usize>, tolerance: f64 },
+}
+
+impl RuleIndex {
+    fn allows(&self, i: usize, j: usize) -> bool {
+        match self {
+            RuleIndex::Key(keys) => keys[i] == keys[j],
+            RuleIndex::Rank { rank, window, .. } => rank[i].abs_diff(rank[j]) < *window,
+            RuleIndex::Length { lengths, tolerance } => within_band(lengths[i], lengths[j], *tolerance),
+        }
+    }
+
+    /// Every pair (i < j) the rule allows, generated without visiting the others
+    fn pairs(&self) -> Vec<(usize, usize)> {
+        let ordered = |a: usize, b: usize| (a.min(b), a.max(b));
+        let mut pairs = Vec::new();
+        match self {
+            RuleIndex::Key(keys) => {
+                let mut blocks: HashMap<&str, Vec<usize>> = HashMap::new();
+                for (id, key) in keys.iter().enumerate() {
+                    blocks.entry(key.as_str()).or_default().push(id);
+                }
+                for ids in blocks.values() {
+                    for (pos, &i) in ids.iter().enumerate() {
+                        pairs.extend(ids[pos + 1..].iter().map(|&j| (i, j)));
+                    }
+                }
+            }
+            RuleIndex::Rank { order, window, .. } => {
+                for (pos, &i) in order.iter().enumerate() {
+                    let end = (pos + *window).min(order.len());
+                    pairs.extend(order[pos + 1..end].iter().map(|&j| ordered(i, j)));
+                }
+            }
+            RuleIndex::Length { lengths, tolerance } => {
+                let mut order: Vec<usize> = (0..lengths.len()).collect();
+                order.sort_by_key(|&id| lengths[id]);
+                for (pos, &i) in order.iter().enumerate() {
+                    // Sorted by length, so the band ends at the first text too long
+                    for &j in &order[pos + 1..] {
+                        if !within_band(lengths[i], lengths[j], *tolerance) {
+                            break;
+                        }
+                        pairs.push(ordered(i, j));
+                    }
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// Candidate generator for a set of texts under a list of blocking rules
+pub struct Blocker {
+    len: usize,
+    rules: Vec<RuleIndex>,
+}
+
+impl Blocker {
+    pub fn new(rules: &[BlockingRule], texts: &[String]) -> Result<Self, String> {
+        let blocking_texts: Vec<String> = texts.iter().map(|text| blocking_text(text)).collect();
+
+        let rules = rules
+            .iter()
+            .map(|rule| -> Result<RuleIndex, String> {
+                let keys = |key: &dyn Fn(&str) -> String| RuleIndex::Key(blocking_texts.iter().map(|t| key(t)).collect());
+                Ok(match rule {
+                    BlockingRule::Prefix { length } => keys(&|text| text.chars().filter(|c| *c != ' ').take(*length).collect()),
+                    BlockingRule::Phonetic { words } => keys(&|text| soundex_words(text, *words)),
+                    BlockingRule::Key { expression } => {
+                        let expression = KeyExpression::parse(expression)?;
+                        keys(&|text| expression.eval(text))
+                    }
+                    BlockingRule::SortedNeighbourhood { window } => {
+                        let mut order: Vec<usize> = (0..texts.len()).collect();
+                        order.sort_by(|&a, &b| blocking_texts[a].cmp(&blocking_texts[b]));
+                        let mut rank = vec![0; texts.len()];
+                        for (pos, &id) in order.iter().enumerate() {
+                            rank[id] = pos;
+                        }
+                        RuleIndex::Rank { order, rank, window: *window }
+                    }
+                    BlockingRule::LengthBand { tolerance } => RuleIndex::Length {
+                        lengths: blocking_texts.iter().map(|text| text.chars().count()).collect(),
+                        tolerance: *tolerance,
+                    },
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { len: texts.len(), rules })
+    }
+
+    /// Whether a pair passes every rule
+    pub fn allows(&self, i: usize, j: usize) -> bool {
+        self.rules.iter().all(|rule| rule.allows(i, j))
+    }
+
+    /// Candidate partners of every text: for text i, the ids j > i to compare it with, ascending
+    pub fn candidates(&self) -> Vec<Vec<usize>> {
+        let mut candidates = vec![Vec::new(); self.len];
+        match self.rules.split_first() {
+            None => {
+                for (i, partners) in candidates.iter_mut().enumerate() {
+                    partners.extend(i + 1..self.len);
+                }
+            }
+            Some((first, rest)) => {
+                for (i, j) in first.pairs() {
+                    if rest.iter().all(|rule| rule.allows(i, j)) {
+                        candidates[i].push(j);
+                    }
+                }
+                for partners in &mut candidates {
+                    partners.sort_unstable();
+                    partners.dedup();
+                }
+            }
+        }
+        candidates
+    }
+}
+
+/// Text the rules look at: lowercase alphanumeric words separated by single spaces
+pub fn blocking_text(text: &str) -> String {
+    let lowered: String = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    lowered.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn soundex_words(text: &str, words: usize) -> String {
+    let soundex = Soundex::default();
+    text.split_whitespace()
+        .take(words)
+        .map(|word| soundex.encode(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn within_band(a: usize, b: usize, tolerance: f64) -> bool {
+    a.abs_diff(b) as f64 <= tolerance * a.max(b) as f64
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rules_prune_candidates() {
+        let texts = texts(&[
+            "Robert Smith, Boston",
+            "Rupert Smith, Boston",
+            "Alice Jones, Madrid and elsewhere in Spain",
+            "Rob Smith",
+        ]);
+
+        let phonetic = Blocker::new(&[BlockingRule::Phonetic { words: 1 }], &texts).unwrap();
+        assert_eq!(phonetic.candidates(), vec![vec![1], vec![], vec![], vec![]]);
+
+        let prefix = Blocker::new(&[BlockingRule::Prefix { length: 3 }], &texts).unwrap();
+        assert_eq!(prefix.candidates(), vec![vec![3], vec![], vec![], vec![]]);
+
+        // "rob" shares the prefix but is half as long
+        let combined = Blocker::new(
+            &[BlockingRule::Prefix { length: 3 }, BlockingRule::LengthBand { tolerance: 0.2 }],
+            &texts,
+        )
+        .unwrap();
+        assert!(combined.candidates().iter().all(Vec::is_empty));
+
+        let neighbours = Blocker::new(&[BlockingRule::SortedNeighbourhood { window: 2 }], &texts).unwrap();
+        // Sorted: alice(2), rob(3), robert(0), rupert(1)
+        assert_eq!(neighbours.candidates(), vec![vec![1, 3], vec![], vec![3], vec![]]);
+        assert!(Blocker::new(&[], &texts).unwrap().allows(0, 2));
+    }
+
+    #[test]
+    fn test_key_expressions() {
+        let expr = KeyExpression::parse("(concat (prefix 2 (word 0)) (soundex (word -1)) (length-bucket 10))").unwrap();
+        assert_eq!(expr.eval(&blocking_text("Robert Smith, Boston")), "ro|B235|1");
+        assert_eq!(KeyExpression::parse("(initials (sorted text))").unwrap().eval("c b a"), "abc");
+        assert!(KeyExpression::parse("(prefix x text)").is_err());
+        assert!(KeyExpression::parse("(shout text)").is_err());
+        assert!(Blocker::new(&[BlockingRule::Key { expression: "(word)".to_string() }], &[]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected_with_strategy() {
+        let mut classifier = crate::core::classifier::TextClassifier::default();
+        let strategy = crate::state::DedupStrategySettings {
+            blocking: Some(vec![BlockingRule::Prefix { length: 3 }, BlockingRule::Key { expression: "(word)".to_string() }]),
+            ..Default::default()
+        };
+        let error = classifier.update_strategy(strategy).unwrap_err();
+        assert!(matches!(error.details, Some(crate::error::ErrorDetails::InvalidStrategy { ref field, .. }) if field == "blocking[1]"));
+        assert!(classifier.get_strategy().blocking.is_none());
+    }
+}
@@ -1,52 +1,88 @@
 // Smart classifier for content type detection
 pub struct SmartClassifier;
 // use std::cell::RefCell;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::OnceLock;
 use rayon::prelude::*;
-use crate::core::semantic::SemanticAnalyzer;
-use crate::state::{DedupStrategySettings, SplitStrategy, ComparisonScope, SimilarityMethod, FuzzyAlgorithm};
+use crate::core::blocking::{Blocker, BlockingStats};
+use crate::core::code::{self, CodeClone, DEFAULT_TOKEN_KGRAM_SIZE};
+use crate::core::explain::{NormalizationStep, NormalizationStepKind, PairExplanation, TextExplanation};
+use crate::core::features::extract_features;
+use crate::core::segmentation::{paragraph_ranges, segment_word_ranges, segment_words, SentenceSegmenter};
+use crate::core::weighting::{CorpusStats, TermWeighting};
+use crate::core::winnowing::{self, FingerprintMatch, DEFAULT_KGRAM_SIZE, DEFAULT_WINDOW};
+use crate::core::normalization::NormalizationPipeline;
+use crate::core::language::{self, detect_language, DetectedLanguage, DEFAULT_LANGUAGE};
+use crate::state::{DedupStrategySettings, DocumentMetadata, FeatureExtraction, Span, SpanMatch, SplitStrategy, ComparisonScope, SimilarityMethod, FuzzyAlgorithm};
 use crate::config::DynamicConfig;
-use rust_stemmers::{Algorithm, Stemmer};
-use unicode_normalization::UnicodeNormalization;
-use std::sync::RwLock;
+use crate::error::AppError;
 use strsim::{jaro_winkler, damerau_levenshtein};
 use deunicode::deunicode;
 use triple_accel::levenshtein;
 
+/// Features (units or shingles) extracted from a normalized text, with their counts
+pub type Features = HashMap<String, usize>;
+
+/// A split unit and where it sits in the text it was split from
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextUnit {
+    pub text: String,
+    pub span: Span,
+}
+
 /// Text classifier for detecting duplicates
 pub struct TextClassifier {
     texts: Vec<String>,
+    metadata: Vec<DocumentMetadata>,
     strategy: DedupStrategySettings,
-    semantic_analyzer: RwLock<SemanticAnalyzer>,
     config: DynamicConfig,
+    normalization: NormalizationPipeline, // Compiled from the strategy
+    corpus: OnceLock<CorpusStats>, // Built from the stored texts on first use, reset when they change
 }
 
 impl Default for TextClassifier {
     fn default() -> Self {
         Self {
             texts: Vec::new(),
+            metadata: Vec::new(),
             strategy: DedupStrategySettings::default(),
-            semantic_analyzer: RwLock::new(SemanticAnalyzer::default()),
             config: DynamicConfig::default(),
+            normalization: NormalizationPipeline::for_strategy(&DedupStrategySettings::default()),
+            corpus: OnceLock::new(),
         }
     }
 }
 
 impl TextClassifier {
     /// Create a new text classifier with the given strategy
-    pub fn new(strategy: DedupStrategySettings) -> Self {
+    pub fn new(mut strategy: DedupStrategySettings) -> Self {
+        let config = strategy.config.clone().unwrap_or_default();
+        strategy.config = Some(config.clone());
         Self {
             texts: Vec::new(),
+            metadata: Vec::new(),
+            normalization: NormalizationPipeline::for_strategy(&strategy),
             strategy,
-            semantic_analyzer: RwLock::new(SemanticAnalyzer::default()),
-            config: DynamicConfig::default(),
+            config,
+            corpus: OnceLock::new(),
         }
     }
 
     /// Add a text to the classifier
     pub fn add_text(&mut self, text: String) -> usize {
+        let id = self.texts.len();
+        self.metadata.push(DocumentMetadata {
+            id,
+            language: detect_language(&text),
+        });
         self.texts.push(text);
-        self.texts.len() - 1
+        self.corpus = OnceLock::new();
+        id
+    }
+
+    /// Get the metadata recorded for a text
+    pub fn get_metadata(&self, idx: usize) -> Option<&DocumentMetadata> {
+        self.metadata.get(idx)
     }
 
     /// Get a text by index
@@ -54,6 +90,11 @@ impl TextClassifier {
         self.texts.get(idx).cloned()
     }
 
+    /// Number of stored texts
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
     /// Get all texts
     pub fn get_all_texts(&self) -> Vec<String> {
         self.texts.clone()
@@ -62,86 +103,180 @@ impl TextClassifier {
     /// Clear all texts
     pub fn clear(&mut self) {
         self.texts.clear();
+        self.metadata.clear();
+        self.corpus = OnceLock::new();
     }
 
-    fn normalize_text(&self, text: &str) -> String {
-        let mut normalized = text.to_string();
-
-        // Case sensitivity
-        if !self.strategy.case_sensitive.unwrap_or(true) {
-            normalized = normalized.to_lowercase();
+    /// Language used for stop words and stemming: the detected one when
+    /// detection is enabled and the language is known, English otherwise
+    fn resolve_language<'a>(&self, detected: Option<&'a DetectedLanguage>) -> &'a str {
+        if !self.strategy.language_detection.unwrap_or(true) {
+            return DEFAULT_LANGUAGE;
         }
+        detected
+            .map(|info| info.code.as_str())
+            .filter(|code| language::support(code).is_some())
+            .unwrap_or(DEFAULT_LANGUAGE)
+    }
 
-        // Whitespace handling
-        if self.strategy.ignore_whitespace.unwrap_or(false) {
-            normalized = normalized.split_whitespace().collect::<Vec<_>>().join(" ");
-        }
+    /// Normalize a text with the current strategy, detecting its language
+    pub fn normalize_text(&self, text: &str) -> String {
+        let detected = if self.strategy.language_detection.unwrap_or(true) {
+            detect_language(text)
+        } else {
+            None
+        };
+        self.normalize_with_language(text, self.resolve_language(detected.as_ref()))
+    }
+
+    fn normalize_with_language(&self, text: &str, lang_code: &str) -> String {
+        self.normalize_traced(text, lang_code, |_, _, _| {})
+    }
+
+    /// Normalize text, reporting each step's output (and whether it ran) to `record`
+    fn normalize_traced(
+        &self,
+        text: &str,
+        lang_code: &str,
+        record: impl FnMut(NormalizationStepKind, bool, &str),
+    ) -> String {
+        self.normalization.run(text, lang_code, &self.config, record)
+    }
 
-        // Punctuation handling
-        if self.strategy.ignore_punctuation.unwrap_or(false) {
-            normalized = normalized.chars().filter(|c| !c.is_ascii_punctuation()).collect();
+    fn split_text_by_strategy(&self, text: &str, strategy: SplitStrategy) -> Vec<String> {
+        self.split_units_by_strategy(text, strategy)
+            .into_iter()
+            .map(|unit| unit.text)
+            .collect()
+    }
+
+    /// Split text into units, keeping the byte and char offsets of each one
+    fn split_units_by_strategy(&self, text: &str, strategy: SplitStrategy) -> Vec<TextUnit> {
+        // Every splitter reports the byte range of its units
+        let ranges: Vec<(usize, usize)> = match strategy {
+            SplitStrategy::Characters => text.char_indices().map(|(idx, c)| (idx, idx + c.len_utf8())).collect(),
+            SplitStrategy::Words => segment_word_ranges(text),
+            SplitStrategy::Sentences => self.sentence_segmenter(text).segment_ranges(text),
+            SplitStrategy::Paragraphs => paragraph_ranges(text, self.config.get_paragraph_delimiters()),
+            SplitStrategy::WholeText => vec![(0, text.len())],
+        };
+        ranges
+            .into_iter()
+            .map(|(start, end)| TextUnit {
+                text: text[start..end].to_string(),
+                span: Span::from_bytes(text, start, end),
+            })
+            .collect()
+    }
+
+    /// Split a stored text into the current analysis units, with offsets
+    pub fn split_units(&self, id: usize) -> Option<Vec<TextUnit>> {
+        let text = self.texts.get(id)?;
+        Some(self.split_units_by_strategy(text, self.strategy.split_strategy))
+    }
+
+    /// Align the units of two stored texts that normalize to the same content,
+    /// pairing repeated units in order of appearance
+    pub fn align_units(&self, original: usize, duplicate: usize) -> Vec<SpanMatch> {
+        let normalized_units = |id: usize| -> Vec<(String, Span)> {
+            let lang_code = self.resolve_language(self.metadata.get(id).and_then(|m| m.language.as_ref()));
+            self.split_units(id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|unit| (self.normalize_with_language(&unit.text, lang_code), unit.span))
+                .filter(|(key, _)| !key.trim().is_empty())
+                .collect()
+        };
+
+        let mut available: HashMap<String, VecDeque<Span>> = HashMap::new();
+        for (key, span) in normalized_units(original) {
+            available.entry(key).or_default().push_back(span);
         }
 
-        // Unicode normalization
-        if self.strategy.normalize_unicode.unwrap_or(false) {
-            normalized = normalized.nfd().collect::<String>();
+        normalized_units(duplicate)
+            .into_iter()
+            .filter_map(|(key, span)| {
+                let original = available.get_mut(&key)?.pop_front()?;
+                Some(SpanMatch { original, duplicate: span })
+            })
+            .collect()
+    }
+
+    /// Sentence segmenter for the text's language and the configured delimiters
+    fn sentence_segmenter(&self, text: &str) -> SentenceSegmenter {
+        let detected = detect_language(text);
+        SentenceSegmenter::new(
+            self.resolve_language(detected.as_ref()),
+            self.config.merge_sentence_delimiters(),
+        )
+    }
+
+    /// Split text into the current analysis units
+    fn split_text(&self, text: &str) -> Vec<String> {
+        self.split_text_by_strategy(text, self.strategy.split_strategy)
+    }
+
+    /// Extract comparison features from a normalized text
+    fn extract_features(&self, normalized: &str) -> Features {
+        let extraction = self.strategy.feature_extraction.unwrap_or_default();
+        let mut features = Features::new();
+        for feature in extract_features(normalized, self.split_text(normalized), extraction) {
+            *features.entry(feature).or_insert(0) += 1;
         }
+        features
+    }
 
-        // Stopwords removal
-        if self.strategy.ignore_stopwords.unwrap_or(false) {
-            let stop_words = self.config.get_stop_words_for_text(&normalized);
-            normalized = normalized
-                .split_whitespace()
-                .filter(|word| !stop_words.contains(&word.to_lowercase()))
-                .collect::<Vec<_>>()
-                .join(" ");
+    /// Winnowed fingerprints of a text, as features keyed by hash
+    fn fingerprint_features(&self, text: &str) -> Features {
+        let mut features = Features::new();
+        for fingerprint in self.fingerprints(text) {
+            *features.entry(fingerprint.hash).or_insert(0) += 1;
         }
+        features
+    }
 
-        // Stemming
-        if self.strategy.stemming.unwrap_or(false) {
-            let en_stemmer = Stemmer::create(Algorithm::English);
-            normalized = normalized
-                .split_whitespace()
-                .map(|word| en_stemmer.stem(word).to_string())
-                .collect::<Vec<_>>()
-                .join(" ");
+    /// Shingle features choose the k-gram unit: word shingles winnow word (or
+    /// code token) k-grams, char shingles winnow character k-grams
+    fn fingerprints(&self, text: &str) -> Vec<winnowing::Fingerprint> {
+        let window = self.strategy.winnowing_window.unwrap_or(DEFAULT_WINDOW);
+        let code = matches!(self.strategy.similarity_method, SimilarityMethod::CodeClone);
+        match self.strategy.feature_extraction {
+            Some(FeatureExtraction::CharShingles(k)) => winnowing::fingerprints(text, k, window),
+            Some(FeatureExtraction::WordShingles(k)) if !code => winnowing::word_fingerprints(text, k, window),
+            _ if code => self.strategy.code.clone().unwrap_or_default().fingerprints(text, self.token_kgram_size(), window),
+            _ => winnowing::fingerprints(text, self.strategy.ngram_size.unwrap_or(DEFAULT_KGRAM_SIZE), window),
         }
+    }
 
-        // Encoding normalization
-        if self.strategy.encoding_normalization.unwrap_or(false) {
-            normalized = normalized.chars()
-                .filter(|c| c.is_ascii() || c.is_alphanumeric())
-                .collect();
+    /// Tokens per k-gram for the CodeClone method
+    fn token_kgram_size(&self) -> usize {
+        match self.strategy.feature_extraction {
+            Some(FeatureExtraction::WordShingles(k)) => k,
+            _ => self.strategy.ngram_size.unwrap_or(DEFAULT_TOKEN_KGRAM_SIZE),
         }
+    }
 
-        normalized
+    /// Compare the winnowed fingerprints of two stored texts
+    pub fn fingerprint_match(&self, id_a: usize, id_b: usize) -> Option<FingerprintMatch> {
+        let a = self.fingerprints(self.texts.get(id_a)?);
+        let b = self.fingerprints(self.texts.get(id_b)?);
+        Some(winnowing::compare(&a, &b))
     }
 
-    fn split_text_by_strategy(&self, text: &str, strategy: SplitStrategy) -> Vec<String> {
-        match strategy {
-            SplitStrategy::Characters => text.chars().map(|c| c.to_string()).collect(),
-            SplitStrategy::Words => text.split_whitespace().map(|s| s.to_string()).collect(),
-            SplitStrategy::Sentences => {
-                let delimiters = self.config.merge_sentence_delimiters();
-                text.split(|c| delimiters.contains(&c))
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect()
-            }
-            SplitStrategy::Paragraphs => {
-                let delimiter = self.config.get_paragraph_delimiters();
-                text.split(delimiter)
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect()
-            }
-            SplitStrategy::WholeText => vec![text.to_string()],
-        }
+    /// Compare two stored texts as source code, with the line ranges of the clone
+    pub fn code_clone(&self, id_a: usize, id_b: usize) -> Option<CodeClone> {
+        let window = self.strategy.winnowing_window.unwrap_or(DEFAULT_WINDOW);
+        let strategy = self.strategy.code.clone().unwrap_or_default();
+        Some(code::compare(self.texts.get(id_a)?, self.texts.get(id_b)?, &strategy, self.token_kgram_size(), window))
     }
 
-    /// Split text into the current analysis units
-    fn split_text(&self, text: &str) -> Vec<String> {
-        self.split_text_by_strategy(text, self.strategy.split_strategy)
+    /// Term weighting used by the weighted cosine methods
+    fn term_weighting(&self) -> Option<TermWeighting> {
+        match self.strategy.similarity_method {
+            SimilarityMethod::TfIdfCosine => Some(TermWeighting::TfIdf),
+            SimilarityMethod::Bm25 => Some(TermWeighting::Bm25),
+            _ => None,
+        }
     }
 
     /// Get the indices of units that belong to the same containing scope
@@ -167,13 +302,11 @@ impl TextClassifier {
             }
             SplitStrategy::Words => {
                 // For words, get all words in the same sentence
-                let sentences = text.split(|c| self.config.merge_sentence_delimiters().contains(&c))
-                    .filter(|s| !s.trim().is_empty())
-                    .collect::<Vec<_>>();
-                
+                let sentences = self.sentence_segmenter(text).segment(text);
+
                 let mut word_pos = 0;
                 for sentence in sentences {
-                    let sentence_words = sentence.split_whitespace().collect::<Vec<_>>();
+                    let sentence_words = segment_words(sentence);
                     if word_pos <= current_idx && current_idx < word_pos + sentence_words.len() {
                         return (word_pos..word_pos + sentence_words.len()).collect();
                     }
@@ -187,13 +320,11 @@ impl TextClassifier {
                     .filter(|s| !s.trim().is_empty())
                     .collect::<Vec<_>>();
                 
+                let segmenter = self.sentence_segmenter(text);
                 let mut sentence_pos = 0;
                 for paragraph in paragraphs {
-                    let paragraph_sentences = paragraph
-                        .split(|c| self.config.merge_sentence_delimiters().contains(&c))
-                        .filter(|s| !s.trim().is_empty())
-                        .collect::<Vec<_>>();
-                    
+                    let paragraph_sentences = segmenter.segment(paragraph);
+
                     if sentence_pos <= current_idx && current_idx < sentence_pos + paragraph_sentences.len() {
                         return (sentence_pos..sentence_pos + paragraph_sentences.len()).collect();
                     }
@@ -209,59 +340,29 @@ impl TextClassifier {
     }
 
     /// Calculate similarity between two texts using the specified method
-    fn calculate_text_similarity(&self, text1: &str, text2: &str) -> f64 {
+    pub fn calculate_text_similarity(&self, text1: &str, text2: &str) -> f64 {
         match self.strategy.similarity_method {
-            SimilarityMethod::Exact => {
-                if text1 == text2 { 1.0 } else { 0.0 }
-            },
-            SimilarityMethod::Levenshtein => {
-                let distance = levenshtein::levenshtein(text1.as_bytes(), text2.as_bytes());
-                let max_len = text1.len().max(text2.len());
-                if max_len == 0 { 1.0 } else { 1.0 - (distance as f64 / max_len as f64) }
+            SimilarityMethod::Exact | SimilarityMethod::Levenshtein | SimilarityMethod::Fuzzy(_) => {
+                string_similarity(&self.strategy.similarity_method, self.strategy.ngram_size, text1, text2).unwrap_or(0.0)
             },
             SimilarityMethod::Semantic => {
                 // Semantic similarity is handled separately through the semantic analyzer
                 0.0
             },
-            SimilarityMethod::Fuzzy(algorithm) => {
-                match algorithm {
-                    FuzzyAlgorithm::DamerauLevenshtein => {
-                        let distance = damerau_levenshtein(text1, text2);
-                        let max_len = text1.len().max(text2.len());
-                        if max_len == 0 { 1.0 } else { 1.0 - (distance as f64 / max_len as f64) }
-                    },
-                    FuzzyAlgorithm::JaroWinkler => {
-                        jaro_winkler(text1, text2)
-                    },
-                    FuzzyAlgorithm::Soundex => {
-                        // Simple phonetic comparison using normalized text
-                        let t1 = deunicode(text1).to_lowercase();
-                        let t2 = deunicode(text2).to_lowercase();
-                        if t1 == t2 { 1.0 } else { 0.0 }
-                    },
-                    FuzzyAlgorithm::NGram => {
-                        let ngram_size = self.strategy.ngram_size.unwrap_or(3);
-                        let t1 = text1.chars().collect::<Vec<_>>();
-                        let t2 = text2.chars().collect::<Vec<_>>();
-                        
-                        if t1.len() < ngram_size || t2.len() < ngram_size {
-                            return if text1 == text2 { 1.0 } else { 0.0 };
-                        }
-
-                        let ngrams1: HashSet<String> = t1.windows(ngram_size)
-                            .map(|w| w.iter().collect::<String>())
-                            .collect();
-                        let ngrams2: HashSet<String> = t2.windows(ngram_size)
-                            .map(|w| w.iter().collect::<String>())
-                            .collect();
-
-                        let intersection = ngrams1.intersection(&ngrams2).count() as f64;
-                        let union = ngrams1.union(&ngrams2).count() as f64;
-
-                        if union == 0.0 { 0.0 } else { intersection / union }
-                    }
+            SimilarityMethod::TfIdfCosine | SimilarityMethod::Bm25 => {
+                let features = [self.extract_features(text1), self.extract_features(text2)];
+                if self.texts.is_empty() {
+                    // Without stored texts, the pair itself provides the document frequencies
+                    return self.feature_similarity(&features[0], &features[1], &CorpusStats::new(&features));
                 }
-            }
+                let corpus = self.corpus.get_or_init(|| CorpusStats::new(&self.index()));
+                self.feature_similarity(&features[0], &features[1], corpus)
+            },
+            SimilarityMethod::Winnowing | SimilarityMethod::CodeClone => {
+                let fingerprints1 = self.fingerprint_features(text1);
+                let fingerprints2 = self.fingerprint_features(text2);
+                self.feature_similarity(&fingerprints1, &fingerprints2, &CorpusStats::default())
+            },
         }
     }
 
@@ -279,94 +380,198 @@ impl TextClassifier {
         self.calculate_text_similarity(&normalized1, &normalized2)
     }
 
-    /// Helper function to check if two texts are similar based on their features
-    fn are_texts_similar(&self, words1: &HashSet<String>, words2: &HashSet<String>, threshold: f64) -> bool {
-        let intersection = words1.intersection(words2).count();
-        let union = words1.union(words2).count();
-        
+    /// Document frequencies over indexed features, used by the weighted cosine methods
+    pub fn corpus_stats(&self, features: &[Features]) -> CorpusStats {
+        match self.term_weighting() {
+            Some(_) => CorpusStats::new(features),
+            None => CorpusStats::default(),
+        }
+    }
+
+    /// Similarity of two feature sets under the current strategy
+    pub fn feature_similarity(&self, words1: &Features, words2: &Features, corpus: &CorpusStats) -> f64 {
+        if let Some(weighting) = self.term_weighting() {
+            return corpus.cosine(words1, words2, weighting);
+        }
+
+        // Winnowing: the larger containment, so a copied passage matches its source
+        if let SimilarityMethod::Winnowing | SimilarityMethod::CodeClone = self.strategy.similarity_method {
+            let hashes1: HashSet<&str> = words1.keys().map(|s| s.as_str()).collect();
+            let hashes2: HashSet<&str> = words2.keys().map(|s| s.as_str()).collect();
+            return winnowing::containment(hashes1.iter().copied(), &hashes2)
+                .max(winnowing::containment(hashes2.iter().copied(), &hashes1));
+        }
+
+        let intersection = words1.keys().filter(|word| words2.contains_key(*word)).count();
+        let union = words1.len() + words2.len() - intersection;
+
         if union == 0 {
-            return false;
+            return 0.0;
         }
-        
+
         let base_similarity = intersection as f64 / union as f64;
-        
+
         // For fuzzy matching, also compare the actual text content
         if let SimilarityMethod::Fuzzy(_) = self.strategy.similarity_method {
-            let text1 = words1.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(" ");
-            let text2 = words2.iter().map(|s| s.as_str()).collect::<Vec<_>>().join(" ");
-            let fuzzy_similarity = self.calculate_text_similarity(&text1, &text2);
-            
+            // Sorted, so the score does not depend on hash map order
+            let joined = |words: &Features| {
+                let mut keys: Vec<&str> = words.keys().map(|s| s.as_str()).collect();
+                keys.sort_unstable();
+                keys.join(" ")
+            };
+            let fuzzy_similarity = self.calculate_text_similarity(&joined(words1), &joined(words2));
+
             // Use a weighted combination of both similarities
-            let combined_similarity = 0.7 * base_similarity + 0.3 * fuzzy_similarity;
-            combined_similarity >= threshold
+            0.7 * base_similarity + 0.3 * fuzzy_similarity
         } else {
-            base_similarity >= threshold
+            base_similarity
         }
     }
 
-    /// Find duplicate texts using the configured strategy
-    pub fn find_duplicates(&mut self) -> Vec<Vec<usize>> {
-        if self.texts.is_empty() {
-            return vec![];
+    /// Helper function to check if two texts are similar based on their features
+    fn are_texts_similar(&self, words1: &Features, words2: &Features, corpus: &CorpusStats, threshold: f64) -> bool {
+        if words1.is_empty() && words2.is_empty() {
+            return false;
         }
+        self.feature_similarity(words1, words2, corpus) >= threshold
+    }
 
-        let threshold = self.strategy.similarity_threshold;
+    /// Normalize and index every text, returning its features by id
+    pub fn index(&self) -> Vec<Features> {
         let use_parallel = self.strategy.use_parallel.unwrap_or_default();
 
-        // First, prepare all the texts in a sequential manner
-        let prepared_texts: Vec<(usize, String, String)> = self.texts.iter()
-            .enumerate()
-            .map(|(idx, text)| {
-                let normalized = self.normalize_text(text);
-                (idx, text.to_string(), normalized)
-            })
-            .collect();
-
-        // Map phase: Extract features from each text
-        let features: Vec<(usize, HashSet<String>)> = if use_parallel {
-            prepared_texts.par_iter()
-                .map(|(idx, _, normalized)| {
-                    let words: HashSet<String> = self.split_text(normalized)
-                        .into_iter()
-                        .collect();
-                    (*idx, words)
-                })
-                .collect()
+        let extract = |(text, metadata): (&String, &DocumentMetadata)| -> Features {
+            let lang_code = self.resolve_language(metadata.language.as_ref());
+            self.document_features(text, &self.normalize_with_language(text, lang_code))
+        };
+
+        if use_parallel {
+            self.texts.par_iter().zip(self.metadata.par_iter()).map(extract).collect()
         } else {
-            prepared_texts.iter()
-                .map(|(idx, _, normalized)| {
-                    let words: HashSet<String> = self.split_text(normalized)
-                        .into_iter()
-                        .collect();
-                    (*idx, words)
-                })
-                .collect()
+            self.texts.iter().zip(self.metadata.iter()).map(extract).collect()
+        }
+    }
+
+    /// Features of a document from its original and normalized text
+    fn document_features(&self, text: &str, normalized: &str) -> Features {
+        // Fingerprints are taken from the original text so positions map back to it
+        if let SimilarityMethod::Winnowing | SimilarityMethod::CodeClone = self.strategy.similarity_method {
+            return self.fingerprint_features(text);
+        }
+        self.extract_features(normalized)
+    }
+
+    /// Explain how two stored texts compare under the current strategy
+    pub fn explain_pair(&self, id_a: usize, id_b: usize) -> Option<PairExplanation> {
+        let explain_text = |id: usize| -> Option<(TextExplanation, Features)> {
+            let text = self.texts.get(id)?;
+            let language = self.resolve_language(self.metadata.get(id)?.language.as_ref());
+            let mut steps = Vec::new();
+            let (normalized, masks) = self.normalization.run_with_masks(text, language, &self.config, |step, applied, output| {
+                steps.push(NormalizationStep { step, applied, output: output.to_string() });
+            });
+            let features = self.document_features(text, &normalized);
+            let explanation = TextExplanation {
+                id,
+                language: language.to_string(),
+                steps,
+                masks,
+                normalized,
+                features: features.iter().map(|(k, &v)| (k.clone(), v)).collect(),
+            };
+            Some((explanation, features))
+        };
+        let (a, features_a) = explain_text(id_a)?;
+        let (b, features_b) = explain_text(id_b)?;
+
+        let corpus = match self.term_weighting() {
+            Some(_) => CorpusStats::new(&self.index()),
+            None => CorpusStats::default(),
+        };
+        let sorted = |features: Vec<&String>| {
+            let mut features: Vec<String> = features.into_iter().cloned().collect();
+            features.sort();
+            features
         };
+        let shared_features = sorted(features_a.keys().filter(|f| features_b.contains_key(*f)).collect());
+        let unique_a = sorted(features_a.keys().filter(|f| !features_b.contains_key(*f)).collect());
+        let unique_b = sorted(features_b.keys().filter(|f| !features_a.contains_key(*f)).collect());
 
-        // Reduce phase: Group similar texts based on comparison scope
+        let union = shared_features.len() + unique_a.len() + unique_b.len();
+        let raw_score = if union == 0 { 0.0 } else { shared_features.len() as f64 / union as f64 };
+        let weighted_score = self.feature_similarity(&features_a, &features_b, &corpus);
+        let threshold = self.strategy.similarity_threshold;
+
+        Some(PairExplanation {
+            text_similarity: self.calculate_text_similarity(&a.normalized, &b.normalized),
+            is_duplicate: self.are_texts_similar(&features_a, &features_b, &corpus, threshold),
+            a,
+            b,
+            shared_features,
+            unique_a,
+            unique_b,
+            method: self.strategy.similarity_method.clone(),
+            raw_score,
+            weighted_score,
+            threshold,
+        })
+    }
+
+    /// Compare indexed texts and cluster similar ones based on comparison scope
+    pub fn cluster(&self, features: &[Features], corpus: &CorpusStats) -> Result<Vec<Vec<usize>>, AppError> {
+        Ok(self.cluster_with_stats(features, corpus)?.0)
+    }
+
+    /// Candidate generator for the strategy's blocking rules, if any are set
+    fn blocker(&self) -> Result<Option<Blocker>, AppError> {
+        let Some(rules) = self.strategy.blocking.as_ref().filter(|rules| !rules.is_empty()) else {
+            return Ok(None);
+        };
+        Blocker::new(rules, &self.texts)
+            .map(Some)
+            .map_err(|e| AppError::invalid_strategy("blocking", e, None))
+    }
+
+    /// Cluster, also counting the comparisons made and those pruned by blocking
+    pub fn cluster_with_stats(&self, features: &[Features], corpus: &CorpusStats) -> Result<(Vec<Vec<usize>>, BlockingStats), AppError> {
+        let threshold = self.strategy.similarity_threshold;
         let mut groups: Vec<Vec<usize>> = Vec::new();
         let mut processed: HashSet<usize> = HashSet::new();
+        let mut stats = BlockingStats::default();
+        let blocker = self.blocker()?;
 
         match self.strategy.comparison_scope {
             ComparisonScope::Global => {
-                // Global comparison: Compare each text with all others
-                for (i, words1) in features.iter() {
-                    if processed.contains(i) {
+                // Blocking stage: candidate partners of each text
+                let candidates = blocker.as_ref().map(Blocker::candidates);
+                if let Some(candidates) = &candidates {
+                    let total_pairs = features.len() * features.len().saturating_sub(1) / 2;
+                    stats.pruned_comparisons = total_pairs - candidates.iter().map(Vec::len).sum::<usize>();
+                }
+
+                // Global comparison: Compare each text with its candidates
+                for (i, words1) in features.iter().enumerate() {
+                    if processed.contains(&i) {
                         continue;
                     }
 
-                    let mut group = vec![*i];
-                    processed.insert(*i);
+                    let mut group = vec![i];
+                    processed.insert(i);
 
-                    // Find similar texts
-                    for (j, words2) in features.iter().skip(*i + 1) {
-                        if processed.contains(j) {
+                    // Find similar texts among the candidates, or all later texts without blocking
+                    let partners: Vec<usize> = match &candidates {
+                        Some(candidates) => candidates[i].clone(),
+                        None => (i + 1..features.len()).collect(),
+                    };
+                    for j in partners {
+                        if processed.contains(&j) {
                             continue;
                         }
 
-                        if self.are_texts_similar(words1, words2, threshold) {
-                            group.push(*j);
-                            processed.insert(*j);
+                        stats.comparisons += 1;
+                        if self.are_texts_similar(words1, &features[j], corpus, threshold) {
+                            group.push(j);
+                            processed.insert(j);
                         }
                     }
 
@@ -386,12 +591,17 @@ impl TextClassifier {
                     processed.insert(i);
 
                     // Get all units that belong to the same containing scope
-                    let scope_units = self.get_local_scope_units(&prepared_texts[i].1, i);
+                    let scope_units = self.get_local_scope_units(&self.texts[i], i);
 
                     // Compare with texts within the same containing unit
                     for &j in &scope_units {
                         if i != j && !processed.contains(&j) && j < features.len() {
-                            if self.are_texts_similar(&features[i].1, &features[j].1, threshold) {
+                            if blocker.as_ref().is_some_and(|blocker| !blocker.allows(i, j)) {
+                                stats.pruned_comparisons += 1;
+                                continue;
+                            }
+                            stats.comparisons += 1;
+                            if self.are_texts_similar(&features[i], &features[j], corpus, threshold) {
                                 group.push(j);
                                 processed.insert(j);
                             }
@@ -405,7 +615,18 @@ impl TextClassifier {
             }
         }
 
-        groups
+        Ok((groups, stats))
+    }
+
+    /// Find duplicate texts using the configured strategy
+    pub fn find_duplicates(&mut self) -> Result<Vec<Vec<usize>>, AppError> {
+        if self.texts.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let features = self.index();
+        let corpus = self.corpus_stats(&features);
+        self.cluster(&features, &corpus)
     }
 
     /// Calculate the appropriate window size based on split strategy
@@ -419,9 +640,17 @@ impl TextClassifier {
         }
     }
 
-    /// Update the strategy
-    pub fn update_strategy(&mut self, strategy: DedupStrategySettings) {
+    /// Update the strategy, rejecting invalid ones. A strategy without a config keeps the current one.
+    pub fn update_strategy(&mut self, mut strategy: DedupStrategySettings) -> Result<(), AppError> {
+        strategy.ensure_valid()?;
+        match &strategy.config {
+            Some(config) => self.config = config.clone(),
+            None => strategy.config = Some(self.config.clone()),
+        }
+        self.normalization = NormalizationPipeline::for_strategy(&strategy);
         self.strategy = strategy;
+        self.corpus = OnceLock::new();
+        Ok(())
     }
 
     /// Get the current strategy
@@ -429,7 +658,72 @@ impl TextClassifier {
         &self.strategy
     }
 
+    /// Replace the dynamic config used for stop words and delimiters
     pub fn update_config(&mut self, config: DynamicConfig) {
+        self.strategy.config = Some(config.clone());
         self.config = config;
+        self.normalization = NormalizationPipeline::for_strategy(&self.strategy);
+        self.corpus = OnceLock::new();
     }
+
+    /// Get the current dynamic config
+    pub fn get_config(&self) -> &DynamicConfig {
+        &self.config
+    }
+}
+
+/// Similarity of two normalized strings under a string metric (exact, edit distance
+/// or fuzzy); None for the feature-based and semantic methods
+pub fn string_similarity(method: &SimilarityMethod, ngram_size: Option<usize>, text1: &str, text2: &str) -> Option<f64> {
+    let similarity = match method {
+        SimilarityMethod::Exact => {
+            if text1 == text2 { 1.0 } else { 0.0 }
+        },
+        SimilarityMethod::Levenshtein => {
+            let distance = levenshtein::levenshtein(text1.as_bytes(), text2.as_bytes());
+            let max_len = text1.len().max(text2.len());
+            if max_len == 0 { 1.0 } else { 1.0 - (distance as f64 / max_len as f64) }
+        },
+        SimilarityMethod::Fuzzy(algorithm) => {
+            match algorithm {
+                FuzzyAlgorithm::DamerauLevenshtein => {
+                    let distance = damerau_levenshtein(text1, text2);
+                    let max_len = text1.len().max(text2.len());
+                    if max_len == 0 { 1.0 } else { 1.0 - (distance as f64 / max_len as f64) }
+                },
+                FuzzyAlgorithm::JaroWinkler => {
+                    jaro_winkler(text1, text2)
+                },
+                FuzzyAlgorithm::Soundex => {
+                    // Simple phonetic comparison using normalized text
+                    let t1 = deunicode(text1).to_lowercase();
+                    let t2 = deunicode(text2).to_lowercase();
+                    if t1 == t2 { 1.0 } else { 0.0 }
+                },
+                FuzzyAlgorithm::NGram => {
+                    let ngram_size = ngram_size.unwrap_or(3);
+                    let t1 = text1.chars().collect::<Vec<_>>();
+                    let t2 = text2.chars().collect::<Vec<_>>();
+
+                    if t1.len() < ngram_size || t2.len() < ngram_size {
+                        return Some(if text1 == text2 { 1.0 } else { 0.0 });
+                    }
+
+                    let ngrams1: HashSet<String> = t1.windows(ngram_size)
+                        .map(|w| w.iter().collect::<String>())
+                        .collect();
+                    let ngrams2: HashSet<String> = t2.windows(ngram_size)
+                        .map(|w| w.iter().collect::<String>())
+                        .collect();
+
+                    let intersection = ngrams1.intersection(&ngrams2).count() as f64;
+                    let union = ngrams1.union(&ngrams2).count() as f64;
+
+                    if union == 0.0 { 0.0 } else { intersection / union }
+                }
+            }
+        },
+        _ => return None,
+    };
+    Some(similarity)
 }
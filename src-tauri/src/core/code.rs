@@ -0,0 +1,409 @@
+//! Source-code clone detection: lexing, Type-2 normalization and token k-gram fingerprints.
+//!
+//! Code is lexed into keywords, identifiers, literals and operators, dropping
+//! comments and whitespace. With normalization, identifiers and literals become
+//! placeholders so renamed copies (Type-2 clones) still match. Token k-grams are
+//! winnowed like character k-grams, keeping char offsets so matches map back to lines.
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::winnowing::{self, Fingerprint};
+
+/// Default k-gram size for code, in tokens
+pub const DEFAULT_TOKEN_KGRAM_SIZE: usize = 8;
+
+/// Placeholder for identifiers under Type-2 normalization
+pub const IDENTIFIER: &str = "$ID";
+/// Placeholder for literals under Type-2 normalization
+pub const LITERAL: &str = "$LIT";
+
+/// Languages the lexer knows the comments, strings and keywords of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CodeLanguage {
+    #[default]
+    Generic, // `//`, `/* */` and `#` comments, C keywords
+    C,
+    Cpp,
+    CSharp,
+    Java,
+    JavaScript,
+    TypeScript,
+    Rust,
+    Go,
+    Python,
+    Ruby,
+    Shell,
+    Sql,
+}
+
+impl CodeLanguage {
+    /// Language of a file extension (without the dot)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Some(match extension.to_ascii_lowercase().as_str() {
+            "c" | "h" => CodeLanguage::C,
+            "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => CodeLanguage::Cpp,
+            "cs" => CodeLanguage::CSharp,
+            "java" => CodeLanguage::Java,
+            "js" | "jsx" | "mjs" | "cjs" => CodeLanguage::JavaScript,
+            "ts" | "tsx" => CodeLanguage::TypeScript,
+            "rs" => CodeLanguage::Rust,
+            "go" => CodeLanguage::Go,
+            "py" => CodeLanguage::Python,
+            "rb" => CodeLanguage::Ruby,
+            "sh" | "bash" | "zsh" => CodeLanguage::Shell,
+            "sql" => CodeLanguage::Sql,
+            _ => return None,
+        })
+    }
+
+    fn syntax(&self) -> Syntax {
+        const C_BLOCK: &[(&str, &str)] = &[("/*", "*/")];
+        let c_like = |keywords| Syntax {
+            line_comments: &["//"],
+            block_comments: C_BLOCK,
+            keywords,
+            ..Syntax::default()
+        };
+        match self {
+            CodeLanguage::Generic => Syntax { line_comments: &["//", "#"], ..c_like(C_KEYWORDS) },
+            CodeLanguage::C | CodeLanguage::Cpp => c_like(C_KEYWORDS),
+            CodeLanguage::CSharp | CodeLanguage::Java => c_like(JAVA_KEYWORDS),
+            CodeLanguage::JavaScript | CodeLanguage::TypeScript => Syntax { backticks: true, ..c_like(JS_KEYWORDS) },
+            CodeLanguage::Rust => Syntax { lifetimes: true, ..c_like(RUST_KEYWORDS) },
+            CodeLanguage::Go => Syntax { backticks: true, ..c_like(GO_KEYWORDS) },
+            CodeLanguage::Python => Syntax {
+                line_comments: &["#"],
+                triple_quotes: true,
+                keywords: PYTHON_KEYWORDS,
+                ..Syntax::default()
+            },
+            CodeLanguage::Ruby => Syntax {
+                line_comments: &["#"],
+                block_comments: &[("=begin", "=end")],
+                keywords: RUBY_KEYWORDS,
+                ..Syntax::default()
+            },
+            CodeLanguage::Shell => Syntax { line_comments: &["#"], keywords: SHELL_KEYWORDS, ..Syntax::default() },
+            CodeLanguage::Sql => Syntax {
+                line_comments: &["--"],
+                block_comments: C_BLOCK,
+                keywords: SQL_KEYWORDS,
+                case_insensitive: true,
+                ..Syntax::default()
+            },
+        }
+    }
+}
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "class", "const", "continue", "default", "delete", "do", "double", "else",
+    "enum", "extern", "false", "float", "for", "goto", "if", "inline", "int", "long", "namespace", "new", "nullptr",
+    "private", "protected", "public", "register", "return", "short", "signed", "sizeof", "static", "struct", "switch",
+    "template", "this", "throw", "true", "try", "catch", "typedef", "typename", "union", "unsigned", "using", "virtual",
+    "void", "volatile", "while",
+];
+const JAVA_KEYWORDS: &[&str] = &[
+    "abstract", "boolean", "break", "byte", "case", "catch", "char", "class", "const", "continue", "default", "do",
+    "double", "else", "enum", "extends", "false", "final", "finally", "float", "for", "foreach", "if", "implements",
+    "import", "in", "instanceof", "int", "interface", "long", "namespace", "new", "null", "override", "package",
+    "private", "protected", "public", "return", "short", "static", "string", "super", "switch", "this", "throw",
+    "throws", "true", "try", "using", "var", "void", "while",
+];
+const JS_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "default", "delete", "do", "else",
+    "export", "extends", "false", "finally", "for", "function", "if", "import", "in", "instanceof", "interface", "let",
+    "new", "null", "of", "return", "super", "switch", "this", "throw", "true", "try", "type", "typeof", "undefined",
+    "var", "void", "while", "yield",
+];
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "false", "fn", "for", "if",
+    "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static",
+    "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+const GO_KEYWORDS: &[&str] = &[
+    "break", "case", "chan", "const", "continue", "default", "defer", "else", "fallthrough", "false", "for", "func",
+    "go", "goto", "if", "import", "interface", "map", "nil", "package", "range", "return", "select", "struct",
+    "switch", "true", "type", "var",
+];
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "self", "try", "while", "with", "yield",
+];
+const RUBY_KEYWORDS: &[&str] = &[
+    "begin", "break", "case", "class", "def", "do", "else", "elsif", "end", "ensure", "false", "for", "if", "in",
+    "module", "next", "nil", "not", "or", "and", "redo", "rescue", "retry", "return", "self", "super", "then", "true",
+    "unless", "until", "when", "while", "yield",
+];
+const SHELL_KEYWORDS: &[&str] = &[
+    "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local", "return",
+    "then", "until", "while",
+];
+const SQL_KEYWORDS: &[&str] = &[
+    "and", "as", "asc", "by", "case", "create", "delete", "desc", "distinct", "else", "end", "exists", "from", "group",
+    "having", "in", "insert", "into", "is", "join", "left", "like", "limit", "not", "null", "on", "or", "order",
+    "outer", "right", "select", "set", "table", "then", "union", "update", "values", "when", "where",
+];
+
+/// Lexical rules of a language
+#[derive(Default)]
+struct Syntax {
+    line_comments: &'static [&'static str],
+    block_comments: &'static [(&'static str, &'static str)],
+    keywords: &'static [&'static str],
+    triple_quotes: bool,     // Python docstrings and long strings
+    backticks: bool,         // Template / raw strings
+    lifetimes: bool,         // `'a` is a lifetime unless closed like a char literal
+    case_insensitive: bool,  // Keywords match in any case
+}
+
+impl Syntax {
+    fn is_keyword(&self, word: &str) -> bool {
+        if self.case_insensitive {
+            self.keywords.iter().any(|keyword| keyword.eq_ignore_ascii_case(word))
+        } else {
+            self.keywords.contains(&word)
+        }
+    }
+}
+
+// ---------------------------------------------------------------------
+// Lexer
+// ---------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenKind {
+    Keyword,
+    Identifier,
+    Literal,
+    Operator,
+}
+
+/// A token and its char offsets in the source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeToken {
+    pub kind: TokenKind,
+    pub text: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Lexes source code, skipping whitespace and comments. Operators are single characters.
+pub fn tokenize(source: &str, language: CodeLanguage) -> Vec<CodeToken> {
+    let syntax = language.syntax();
+    let chars: Vec<char> = source.chars().collect();
+    let starts_with = |pos: usize, marker: &str| marker.chars().enumerate().all(|(i, c)| chars.get(pos + i) == Some(&c));
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        if c.is_whitespace() {
+            pos += 1;
+            continue;
+        }
+        if syntax.line_comments.iter().any(|marker| starts_with(pos, marker)) {
+            while pos < chars.len() && chars[pos] != '\n' {
+                pos += 1;
+            }
+            continue;
+        }
+        if let Some((open, close)) = syntax.block_comments.iter().find(|(open, _)| starts_with(pos, open)) {
+            pos += open.chars().count();
+            while pos < chars.len() && !starts_with(pos, close) {
+                pos += 1;
+            }
+            pos = (pos + close.chars().count()).min(chars.len());
+            continue;
+        }
+
+        let start = pos;
+        let kind = if c.is_alphabetic() || c == '_' || c == '$' {
+            while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_' || chars[pos] == '$') {
+                pos += 1;
+            }
+            let word: String = chars[start..pos].iter().collect();
+            if syntax.is_keyword(&word) { TokenKind::Keyword } else { TokenKind::Identifier }
+        } else if c.is_ascii_digit() {
+            while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_' || chars[pos] == '.') {
+                pos += 1;
+            }
+            TokenKind::Literal
+        } else if syntax.triple_quotes && (starts_with(pos, "\"\"\"") || starts_with(pos, "'''")) {
+            let delimiter: String = chars[pos..pos + 3].iter().collect();
+            pos += 3;
+            while pos < chars.len() && !starts_with(pos, &delimiter) {
+                pos += if chars[pos] == '\\' { 2 } else { 1 };
+            }
+            pos = (pos + 3).min(chars.len());
+            TokenKind::Literal
+        } else if c == '"' || (c == '`' && syntax.backticks) || (c == '\'' && !is_lifetime(&chars, pos, &syntax)) {
+            pos += 1;
+            while pos < chars.len() && chars[pos] != c {
+                pos += if chars[pos] == '\\' { 2 } else { 1 };
+            }
+            pos = (pos + 1).min(chars.len());
+            TokenKind::Literal
+        } else {
+            pos += 1;
+            TokenKind::Operator
+        };
+
+        tokens.push(CodeToken {
+            kind,
+            text: chars[start..pos].iter().collect(),
+            start,
+            end: pos,
+        });
+    }
+    tokens
+}
+
+/// Whether the quote at `pos` starts a Rust lifetime rather than a char literal
+fn is_lifetime(chars: &[char], pos: usize, syntax: &Syntax) -> bool {
+    syntax.lifetimes
+        && chars.get(pos + 1).is_some_and(|c| c.is_alphabetic() || *c == '_')
+        && chars.get(pos + 2) != Some(&'\'')
+}
+
+// ---------------------------------------------------------------------
+// Fingerprints and clones
+// ---------------------------------------------------------------------
+
+/// Code mode settings, stored in `DedupStrategySettings::code`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CodeStrategy {
+    #[serde(default)]
+    pub language: CodeLanguage,
+    #[serde(default)]
+    pub normalize_identifiers: bool, // Type-2: renamed variables and functions still match
+    #[serde(default)]
+    pub normalize_literals: bool, // Type-2: changed constants and strings still match
+}
+
+impl CodeStrategy {
+    /// Text a token contributes to k-grams
+    fn token_text<'a>(&self, token: &'a CodeToken) -> &'a str {
+        match token.kind {
+            TokenKind::Identifier if self.normalize_identifiers => IDENTIFIER,
+            TokenKind::Literal if self.normalize_literals => LITERAL,
+            _ => &token.text,
+        }
+    }
+
+    /// Winnows source code into fingerprints of `k` tokens, with char offsets in the source
+    pub fn fingerprints(&self, source: &str, k: usize, window: usize) -> Vec<Fingerprint> {
+        let tokens = tokenize(source, self.language);
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+        let kgrams: Vec<Fingerprint> = tokens
+            .windows(k.clamp(1, tokens.len()))
+            .map(|gram| Fingerprint {
+                // Unit separator between tokens, so "a b" and "ab" differ
+                hash: winnowing::hash_kgram(gram.iter().map(|token| self.token_text(token)).collect::<Vec<_>>().join("\u{1f}").as_bytes()),
+                start: gram[0].start,
+                end: gram[gram.len() - 1].end,
+            })
+            .collect();
+        winnowing::winnow(&kgrams, window)
+    }
+}
+
+/// Lines `start..=end` of a source (numbered from 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A clone between two sources: containment both ways and the matching line ranges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeClone {
+    pub similarity: f64, // Larger containment, as scored by the CodeClone method
+    pub containment_a: f64,
+    pub containment_b: f64,
+    pub lines_a: Vec<LineRange>,
+    pub lines_b: Vec<LineRange>,
+}
+
+/// Compares the fingerprints of two sources and maps the shared ones to line ranges
+pub fn compare(source_a: &str, source_b: &str, strategy: &CodeStrategy, k: usize, window: usize) -> CodeClone {
+    let matched = winnowing::compare(&strategy.fingerprints(source_a, k, window), &strategy.fingerprints(source_b, k, window));
+    CodeClone {
+        similarity: matched.containment_a.max(matched.containment_b),
+        containment_a: matched.containment_a,
+        containment_b: matched.containment_b,
+        lines_a: line_ranges(source_a, &matched.matched_a),
+        lines_b: line_ranges(source_b, &matched.matched_b),
+    }
+}
+
+/// Lines covered by fingerprints, merging overlapping and adjacent ranges
+pub fn line_ranges(source: &str, fingerprints: &[Fingerprint]) -> Vec<LineRange> {
+    // Char offset where each line starts
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.chars().enumerate().filter(|(_, c)| *c == '\n').map(|(pos, _)| pos + 1))
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset);
+
+    let mut ranges: Vec<LineRange> = fingerprints
+        .iter()
+        .map(|fp| LineRange { start: line_of(fp.start), end: line_of(fp.end.saturating_sub(1).max(fp.start)) })
+        .collect();
+    ranges.sort_by_key(|range| (range.start, range.end));
+
+    let mut merged: Vec<LineRange> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end + 1 => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_skips_comments_and_whitespace() {
+        let tokens = tokenize("let x = \"a // b\"; // note\n/* block */ x += 1;", CodeLanguage::Rust);
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, vec!["let", "x", "=", "\"a // b\"", ";", "x", "+", "=", "1", ";"]);
+        assert_eq!(tokens[0].kind, TokenKind::Keyword);
+        assert_eq!(tokens[3].kind, TokenKind::Literal);
+
+        let python = tokenize("def f(a):  # comment\n    return '''doc'''", CodeLanguage::Python);
+        assert_eq!(python.len(), 8);
+        assert_eq!(python[7].text, "'''doc'''");
+        assert_eq!(tokenize("fn f<'a>(x: &'a str)", CodeLanguage::Rust)[3].text, "'");
+    }
+
+    #[test]
+    fn test_renamed_clone_found_with_line_ranges() {
+        let original = "int total(int* xs, int n) {\n    int sum = 0;\n    for (int i = 0; i < n; i++) {\n        sum += xs[i];\n    }\n    return sum;\n}\n";
+        let copy = format!(
+            "// helpers\nvoid log_it(const char* m) {{ puts(m); }}\n\n{}",
+            original.replace("sum", "acc").replace("xs", "values").replace("0", "1")
+        );
+        let exact = CodeStrategy { language: CodeLanguage::C, ..CodeStrategy::default() };
+        let type2 = CodeStrategy { normalize_identifiers: true, normalize_literals: true, ..exact.clone() };
+
+        let renamed = compare(original, &copy, &exact, 6, 4);
+        let clone = compare(original, &copy, &type2, 6, 4);
+        assert!(renamed.similarity < 0.5);
+        assert_eq!(clone.containment_a, 1.0);
+        // Winnowing need not select the last k-gram, so the ranges may stop short of the final brace
+        assert_eq!(clone.lines_a.len(), 1);
+        assert_eq!(clone.lines_a[0].start, 1);
+        assert_eq!(clone.lines_b.len(), 1);
+        assert_eq!(clone.lines_b[0].start, 4);
+        assert_eq!(clone.lines_b[0].end - clone.lines_a[0].end, 3);
+    }
+}
@@ -0,0 +1,319 @@
+//! Word- and character-level edit scripts between two texts, with moved-block detection.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state::Span;
+
+/// Largest LCS table (tokens × tokens) computed; beyond it the differing middle is one replacement
+const MAX_LCS_CELLS: usize = 16_000_000;
+
+/// Consecutive edits of one kind: (kind, old token range, new token range)
+type Run = (EditKind, (usize, usize), (usize, usize));
+
+/// Unit the edit script is expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DiffGranularity {
+    #[default]
+    Word,
+    Char,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EditKind {
+    Equal,
+    Insert,
+    Delete,
+    Replace,
+    MovedFrom, // Removed here and inserted unchanged where `new` points
+    MovedTo,   // Inserted here, taken unchanged from where `old` points
+}
+
+/// One edit; `old`/`new` are spans of the old and new texts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffEdit {
+    pub kind: EditKind,
+    pub old: Option<Span>,
+    pub new: Option<Span>,
+    pub old_text: String,
+    pub new_text: String,
+}
+
+/// Edit script turning an old text into a new one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextDiff {
+    pub granularity: DiffGranularity,
+    pub edits: Vec<DiffEdit>,
+}
+
+/// Diffs two texts at the given granularity.
+pub fn diff(old: &str, new: &str, granularity: DiffGranularity) -> TextDiff {
+    let old_tokens = tokenize(old, granularity);
+    let new_tokens = tokenize(new, granularity);
+
+    let mut runs: Vec<Run> = Vec::new();
+    let mut push = |kind: EditKind, i: usize, j: usize, di: usize, dj: usize| match runs.last_mut() {
+        Some((last, old_range, new_range)) if *last == kind => {
+            old_range.1 += di;
+            new_range.1 += dj;
+        }
+        _ => runs.push((kind, (i, i + di), (j, j + dj))),
+    };
+    for (kind, i, j) in align(&old_tokens, &new_tokens) {
+        match kind {
+            EditKind::Equal => push(kind, i, j, 1, 1),
+            EditKind::Delete => push(kind, i, j, 1, 0),
+            _ => push(kind, i, j, 0, 1),
+        }
+    }
+
+    // A deletion next to an insertion is a replacement
+    let mut merged: Vec<Run> = Vec::new();
+    for run in runs {
+        match (merged.last_mut(), run.0) {
+            (Some(last), EditKind::Insert | EditKind::Delete)
+                if matches!(last.0, EditKind::Insert | EditKind::Delete) && last.0 != run.0 =>
+            {
+                last.0 = EditKind::Replace;
+                last.1 = (last.1 .0.min(run.1 .0), last.1 .1.max(run.1 .1));
+                last.2 = (last.2 .0.min(run.2 .0), last.2 .1.max(run.2 .1));
+            }
+            _ => merged.push(run),
+        }
+    }
+
+    let span_of = |text: &str, tokens: &[(usize, &str)], (from, to): (usize, usize)| -> Span {
+        let start = tokens.get(from).map_or(text.len(), |&(pos, _)| pos);
+        let end = if to == 0 { start } else { tokens[to - 1].0 + tokens[to - 1].1.len() };
+        Span::from_bytes(text, start, end.max(start))
+    };
+
+    let mut edits: Vec<DiffEdit> = merged
+        .into_iter()
+        .map(|(kind, old_range, new_range)| {
+            let old_span = span_of(old, &old_tokens, old_range);
+            let new_span = span_of(new, &new_tokens, new_range);
+            DiffEdit {
+                kind,
+                old: (kind != EditKind::Insert).then_some(old_span),
+                new: (kind != EditKind::Delete).then_some(new_span),
+                old_text: old[old_span.start..old_span.end].to_string(),
+                new_text: new[new_span.start..new_span.end].to_string(),
+            }
+        })
+        .collect();
+
+    detect_moves(&mut edits);
+    TextDiff { granularity, edits }
+}
+
+/// Pairs each deleted block with an inserted block elsewhere that has the same
+/// content, ignoring the whitespace and punctuation at its ends
+fn detect_moves(edits: &mut [DiffEdit]) {
+    let content = |text: &str| text.trim_matches(|c: char| !c.is_alphanumeric()).to_string();
+    let mut used = HashSet::new();
+    for d in 0..edits.len() {
+        if edits[d].kind != EditKind::Delete {
+            continue;
+        }
+        let moved = content(&edits[d].old_text);
+        if moved.is_empty() {
+            continue;
+        }
+        let target = (0..edits.len()).find(|&i| {
+            !used.contains(&i) && edits[i].kind == EditKind::Insert && content(&edits[i].new_text) == moved
+        });
+        if let Some(i) = target {
+            used.insert(i);
+            let (from, to) = (edits[d].old, edits[i].new);
+            edits[d].kind = EditKind::MovedFrom;
+            edits[d].new = to;
+            edits[d].new_text = edits[i].new_text.clone();
+            edits[i].kind = EditKind::MovedTo;
+            edits[i].old = from;
+            edits[i].old_text = edits[d].old_text.clone();
+        }
+    }
+}
+
+/// Splits text into tokens with their byte offsets. Word tokens are runs of
+/// alphanumerics, runs of whitespace or single other characters, so the
+/// tokens always concatenate back to the text.
+fn tokenize(text: &str, granularity: DiffGranularity) -> Vec<(usize, &str)> {
+    if granularity == DiffGranularity::Char {
+        return text.char_indices().map(|(i, c)| (i, &text[i..i + c.len_utf8()])).collect();
+    }
+
+    let class = |c: char| {
+        if c.is_alphanumeric() {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        if let Some(p) = prev {
+            if class(p) != class(c) || class(c) == 2 {
+                tokens.push((start, &text[start..i]));
+                start = i;
+            }
+        }
+        prev = Some(c);
+    }
+    if !text.is_empty() {
+        tokens.push((start, &text[start..]));
+    }
+    tokens
+}
+
+/// Token-level alignment as (Equal | Delete | Insert, old index, new index),
+/// using the longest common subsequence of the part between the common prefix and suffix
+fn align(old: &[(usize, &str)], new: &[(usize, &str)]) -> Vec<(EditKind, usize, usize)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a.1 == b.1).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a.1 == b.1)
+        .count();
+    let (n, m) = (old.len() - prefix - suffix, new.len() - prefix - suffix);
+
+    let mut ops: Vec<(EditKind, usize, usize)> = (0..prefix).map(|k| (EditKind::Equal, k, k)).collect();
+
+    if n * m > MAX_LCS_CELLS {
+        ops.extend((prefix..prefix + n).map(|i| (EditKind::Delete, i, prefix)));
+        ops.extend((prefix..prefix + m).map(|j| (EditKind::Insert, prefix + n, j)));
+    } else {
+        // lcs[i][j] = LCS length of old[prefix + i..] and new[prefix + j..] within the middle
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old[prefix + i].1 == new[prefix + j].1 {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old[prefix + i].1 == new[prefix + j].1 {
+                ops.push((EditKind::Equal, prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+                ops.push((EditKind::Insert, prefix + i, prefix + j));
+                j += 1;
+            } else {
+                ops.push((EditKind::Delete, prefix + i, prefix + j));
+                i += 1;
+            }
+        }
+    }
+
+    let (old_end, new_end) = (prefix + n, prefix + m);
+    ops.extend((0..suffix).map(|k| (EditKind::Equal, old_end + k, new_end + k)));
+    ops
+}
+
+impl TextDiff {
+    /// Inline HTML rendering: `<del>` for removed text, `<ins>` for added text,
+    /// with `class="moved"` on moved blocks.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<div class=\"diff\">");
+        for edit in &self.edits {
+            match edit.kind {
+                EditKind::Equal => html.push_str(&escape_html(&edit.new_text)),
+                EditKind::Insert => html.push_str(&format!("<ins>{}</ins>", escape_html(&edit.new_text))),
+                EditKind::Delete => html.push_str(&format!("<del>{}</del>", escape_html(&edit.old_text))),
+                EditKind::Replace => html.push_str(&format!(
+                    "<del>{}</del><ins>{}</ins>",
+                    escape_html(&edit.old_text),
+                    escape_html(&edit.new_text)
+                )),
+                EditKind::MovedFrom => {
+                    html.push_str(&format!("<del class=\"moved\">{}</del>", escape_html(&edit.old_text)))
+                }
+                EditKind::MovedTo => {
+                    html.push_str(&format!("<ins class=\"moved\">{}</ins>", escape_html(&edit.new_text)))
+                }
+            }
+        }
+        html.push_str("</div>");
+        html
+    }
+}
+
+/// Escapes text for use in HTML element content and attributes
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(diff: &TextDiff) -> Vec<EditKind> {
+        diff.edits.iter().map(|edit| edit.kind).collect()
+    }
+
+    #[test]
+    fn test_word_diff_reports_replacements_and_inserts() {
+        let result = diff("the quick brown fox", "the slow brown fox jumps", DiffGranularity::Word);
+        assert_eq!(
+            kinds(&result),
+            vec![EditKind::Equal, EditKind::Replace, EditKind::Equal, EditKind::Insert]
+        );
+        assert_eq!(result.edits[1].old_text, "quick");
+        assert_eq!(result.edits[1].new_text, "slow");
+        assert_eq!(result.edits[3].new_text, " jumps");
+        assert_eq!(
+            result.to_html(),
+            "<div class=\"diff\">the <del>quick</del><ins>slow</ins> brown fox<ins> jumps</ins></div>"
+        );
+    }
+
+    #[test]
+    fn test_moved_block_is_detected() {
+        let result = diff(
+            "Terms apply. Shipping is free. Call us today.",
+            "Shipping is free. Terms apply. Call us today.",
+            DiffGranularity::Word,
+        );
+        let moved_from = result.edits.iter().find(|edit| edit.kind == EditKind::MovedFrom).unwrap();
+        let moved_to = result.edits.iter().find(|edit| edit.kind == EditKind::MovedTo).unwrap();
+        assert_eq!(moved_from.old, moved_to.old);
+        assert_eq!(moved_from.new, moved_to.new);
+    }
+
+    #[test]
+    fn test_char_diff_offsets() {
+        let result = diff("café", "cafe", DiffGranularity::Char);
+        let replace = result.edits.iter().find(|edit| edit.kind == EditKind::Replace).unwrap();
+        let old = replace.old.unwrap();
+        assert_eq!((old.start, old.end, old.char_start, old.char_end), (3, 5, 3, 4));
+        assert_eq!(replace.new_text, "e");
+    }
+}
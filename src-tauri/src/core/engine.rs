@@ -1,74 +1,241 @@
 // Main deduplication engine
-use crate::core::classifier::TextClassifier;
-use crate::state::DedupStrategySettings;
-use std::collections::HashMap;
+//! Single pipeline orchestrator behind every command:
+//! ingest → normalize → index → compare → cluster → report.
+use crate::config::DynamicConfig;
+use crate::core::blocking::BlockingStats;
+use crate::core::classifier::{Features, TextClassifier};
+use crate::core::code::CodeClone;
+use crate::core::diff::{self, DiffGranularity, TextDiff};
+use crate::core::explain::PairExplanation;
+use crate::core::logs::{LogMiner, LogResults};
+use crate::core::records::{Record, RecordMatcher, RecordResults};
+use crate::core::weighting::CorpusStats;
+use crate::core::winnowing::FingerprintMatch;
+use crate::error::AppError;
+use crate::state::{
+    DedupResults, DedupStats, DedupStrategySettings, DocumentMetadata, DuplicateGroup, DuplicateMember, Span,
+};
 
 pub struct DeduplicationEngine {
     classifier: TextClassifier,
-    texts: HashMap<String, String>,
+    records: Vec<Record>,
 }
 
 impl DeduplicationEngine {
     pub fn new(strategy: DedupStrategySettings) -> Self {
         Self {
             classifier: TextClassifier::new(strategy),
-            texts: HashMap::new(),
+            records: Vec::new(),
         }
     }
 
-    pub fn update_strategy(&mut self, strategy: DedupStrategySettings) {
-        println!("Engine: Updating strategy to {:?}", strategy);
-        // Create new classifier with updated strategy and clear any existing content
-        self.classifier = TextClassifier::new(strategy);
-        self.clear_duplicates();
+    /// Ingest stage: store a text and return its id
+    pub fn ingest(&mut self, text: String) -> usize {
+        self.classifier.add_text(text)
     }
 
-    pub fn process_text(&mut self, content: String) -> usize {
-        println!("Engine: Processing text: {}", content);
-        // Clear existing content before processing new text
-        self.classifier.clear();
-        let id = self.classifier.add_text(content.clone());
-        self.add_text(id.to_string(), content.clone());
-        id
-    }
-
-    pub fn get_duplicates(&mut self) -> Vec<Vec<String>> {
-        let dupes = self.classifier.find_duplicates();
-        dupes.iter()
-            .map(|group| {
-                group.iter()
-                    .filter_map(|&idx| self.classifier.get_text(idx))
-                    .collect()
-            })
-            .collect()
+    /// Replace the strategy, keeping the ingested texts
+    pub fn update_strategy(&mut self, strategy: DedupStrategySettings) -> Result<(), AppError> {
+        self.classifier.update_strategy(strategy)
     }
 
     pub fn get_strategy(&self) -> &DedupStrategySettings {
         self.classifier.get_strategy()
     }
 
-    pub fn clear_duplicates(&mut self) {
-        self.classifier.clear();
+    pub fn get_config(&self) -> &DynamicConfig {
+        self.classifier.get_config()
+    }
+
+    /// Apply a dynamic config (stop words, delimiters) to the pipeline
+    pub fn update_config(&mut self, config: DynamicConfig) {
+        self.classifier.update_config(config);
+    }
+
+    pub fn get_text(&self, id: usize) -> Option<String> {
+        self.classifier.get_text(id)
+    }
+
+    pub fn get_metadata(&self, id: usize) -> Option<DocumentMetadata> {
+        self.classifier.get_metadata(id).cloned()
+    }
+
+    /// Winnowing fingerprints shared by two texts, with containment scores
+    pub fn fingerprint_match(&self, id_a: usize, id_b: usize) -> Option<FingerprintMatch> {
+        self.classifier.fingerprint_match(id_a, id_b)
     }
 
-    pub fn add_text(&mut self, id: String, text: String) -> String {
-        self.texts.insert(id, text.clone());
-        text
+    /// Token clone between two texts read as source code, with matching line ranges
+    pub fn code_clone(&self, id_a: usize, id_b: usize) -> Option<CodeClone> {
+        self.classifier.code_clone(id_a, id_b)
     }
 
-    pub fn get_text(&self, id: &str) -> Option<String> {
-        self.texts.get(id).cloned()
+    /// Step-by-step diagnostics for a pair under the current strategy
+    pub fn explain_pair(&self, id_a: usize, id_b: usize) -> Option<PairExplanation> {
+        self.classifier.explain_pair(id_a, id_b)
+    }
+
+    /// Edit script turning text `id_a` (e.g. a group's original) into text `id_b`
+    pub fn diff(&self, id_a: usize, id_b: usize, granularity: DiffGranularity) -> Option<TextDiff> {
+        let old = self.classifier.get_text(id_a)?;
+        let new = self.classifier.get_text(id_b)?;
+        Some(diff::diff(&old, &new, granularity))
     }
 
     pub fn get_texts(&self) -> Vec<String> {
-        self.texts.values().cloned().collect()
+        self.classifier.get_all_texts()
+    }
+
+    pub fn len(&self) -> usize {
+        self.classifier.len()
     }
 
-    pub fn get_text_ids(&self) -> Vec<String> {
-        self.texts.keys().cloned().collect()
+    pub fn is_empty(&self) -> bool {
+        self.classifier.len() == 0
     }
 
     pub fn clear(&mut self) {
-        self.texts.clear();
+        self.classifier.clear();
+        self.records.clear();
+    }
+
+    /// Ingest stage for record mode: store a record and return its id
+    pub fn ingest_record(&mut self, record: Record) -> usize {
+        self.records.push(record);
+        self.records.len() - 1
+    }
+
+    pub fn get_record(&self, id: usize) -> Option<Record> {
+        self.records.get(id).cloned()
+    }
+
+    /// Group duplicate records with the strategy's record settings (None without them)
+    pub fn run_records(&self) -> Option<RecordResults> {
+        let strategy = self.get_strategy().record.as_ref()?;
+        Some(RecordMatcher::new(strategy, self.get_config().clone()).deduplicate(&self.records))
+    }
+
+    /// Mine line templates from the ingested texts with the strategy's log settings (None without them)
+    pub fn run_logs(&self) -> Option<LogResults> {
+        let strategy = self.get_strategy().log.as_ref()?;
+        let mut miner = LogMiner::new(strategy, &self.get_config().user_masking_rules);
+        for (id, text) in self.get_texts().iter().enumerate() {
+            miner.add_text(id, text);
+        }
+        Some(miner.finish())
+    }
+
+    /// Read access to the underlying classifier
+    pub fn classifier(&self) -> &TextClassifier {
+        &self.classifier
+    }
+
+    /// Run the full pipeline over the ingested texts
+    pub fn run(&self) -> Result<DedupResults, AppError> {
+        if self.is_empty() {
+            return Ok(DedupResults {
+                duplicate_groups: Vec::new(),
+                stats: DedupStats {
+                    total_items: 0,
+                    unique_items: 0,
+                    duplicate_groups: 0,
+                    comparisons: 0,
+                    pruned_comparisons: 0,
+                },
+            });
+        }
+
+        // Normalize + index
+        let features = self.classifier.index();
+        let corpus = self.classifier.corpus_stats(&features);
+
+        self.run_indexed(&features, &corpus)
+    }
+
+    /// Run the stages after indexing, over features already indexed with the current strategy
+    pub fn run_indexed(&self, features: &[Features], corpus: &CorpusStats) -> Result<DedupResults, AppError> {
+        // Block + compare + cluster
+        let (mut clusters, blocking) = self.classifier.cluster_with_stats(features, corpus)?;
+
+        // Apply max_duplicate_count limit if specified
+        if let Some(max_count) = self.get_strategy().max_duplicate_count {
+            clusters.truncate(max_count);
+        }
+
+        Ok(self.report(features, corpus, clusters, blocking))
+    }
+
+    /// Report stage: turn clusters of ids into frontend-friendly groups and stats
+    fn report(
+        &self,
+        features: &[Features],
+        corpus: &CorpusStats,
+        clusters: Vec<Vec<usize>>,
+        blocking: BlockingStats,
+    ) -> DedupResults {
+        let duplicate_groups: Vec<DuplicateGroup> = clusters
+            .into_iter()
+            .filter(|indices| indices.len() > 1)
+            .map(|indices| {
+                let original_idx = indices[0];
+                let members: Vec<DuplicateMember> = indices[1..]
+                    .iter()
+                    .map(|&idx| self.member(features, corpus, original_idx, idx))
+                    .collect();
+                let similarity = members.iter().map(|member| member.similarity).sum::<f64>() / members.len() as f64;
+
+                let texts: Vec<String> = indices
+                    .iter()
+                    .filter_map(|&idx| self.classifier.get_text(idx))
+                    .collect();
+
+                DuplicateGroup {
+                    original: texts[0].clone(),
+                    duplicates: texts[1..].to_vec(),
+                    similarity,
+                    original_id: original_idx,
+                    members,
+                }
+            })
+            .collect();
+
+        let total_items = self.len();
+        let duplicate_groups_count = duplicate_groups.len();
+        let unique_items = total_items
+            - duplicate_groups
+                .iter()
+                .map(|group| group.duplicates.len())
+                .sum::<usize>();
+
+        DedupResults {
+            duplicate_groups,
+            stats: DedupStats {
+                total_items,
+                unique_items,
+                duplicate_groups: duplicate_groups_count,
+                comparisons: blocking.comparisons,
+                pruned_comparisons: blocking.pruned_comparisons,
+            },
+        }
+    }
+
+    /// Locate a duplicate in its document and align its units with the original
+    fn member(&self, features: &[Features], corpus: &CorpusStats, original: usize, id: usize) -> DuplicateMember {
+        let matches = self.classifier.align_units(original, id);
+        let span = matches
+            .iter()
+            .map(|m| m.duplicate)
+            .reduce(|a, b| a.cover(&b))
+            .unwrap_or_else(|| {
+                let text = self.classifier.get_text(id).unwrap_or_default();
+                Span::from_bytes(&text, 0, text.len())
+            });
+
+        DuplicateMember {
+            id,
+            similarity: self.classifier.feature_similarity(&features[original], &features[id], corpus),
+            span,
+            matches,
+        }
     }
 }
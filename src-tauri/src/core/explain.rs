@@ -0,0 +1,60 @@
+//! Diagnostics explaining why two texts were (or were not) grouped.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::masking::MaskHit;
+use crate::state::SimilarityMethod;
+
+/// Normalization steps, in the order the classifier applies them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NormalizationStepKind {
+    Masking,
+    Domain,
+    Case,
+    Whitespace,
+    Punctuation,
+    Unicode,
+    Stopwords,
+    Stemming,
+    Encoding,
+}
+
+/// Output of one normalization step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizationStep {
+    pub step: NormalizationStepKind,
+    pub applied: bool, // False when the strategy disables the step; output is then unchanged
+    pub output: String,
+}
+
+/// How one text of the pair went through the pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextExplanation {
+    pub id: usize,
+    pub language: String, // Language used for stop words and stemming
+    pub steps: Vec<NormalizationStep>,
+    pub masks: Vec<MaskHit>, // Masking rules that fired, with their match counts
+    pub normalized: String,
+    pub features: BTreeMap<String, usize>,
+}
+
+/// Step-by-step account of a pair comparison under the current strategy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairExplanation {
+    pub a: TextExplanation,
+    pub b: TextExplanation,
+    pub shared_features: Vec<String>,
+    pub unique_a: Vec<String>,
+    pub unique_b: Vec<String>,
+    pub method: SimilarityMethod,
+    /// Jaccard overlap of the feature sets
+    pub raw_score: f64,
+    /// Score used for grouping (weighted, fuzzy-blended or containment, per method)
+    pub weighted_score: f64,
+    /// Method applied to the whole normalized texts
+    pub text_similarity: f64,
+    pub threshold: f64,
+    pub is_duplicate: bool,
+}
@@ -0,0 +1,43 @@
+//! Feature extraction: turns a normalized text into the units compared by set-based methods.
+
+use crate::core::segmentation::segment_words;
+use crate::state::FeatureExtraction;
+
+/// Extracts comparison features from a normalized text and its split units.
+///
+/// Shingles keep word or character order, so "dog bites man" and
+/// "man bites dog" no longer produce the same features.
+pub fn extract_features(normalized: &str, units: Vec<String>, extraction: FeatureExtraction) -> Vec<String> {
+    match extraction {
+        FeatureExtraction::BagOfUnits => units,
+        FeatureExtraction::WordShingles(k) => word_shingles(normalized, k),
+        FeatureExtraction::CharShingles(k) => char_shingles(normalized, k),
+    }
+}
+
+/// Contiguous k-grams of words. Texts shorter than k yield a single shingle.
+pub fn word_shingles(text: &str, k: usize) -> Vec<String> {
+    let words = segment_words(text);
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let k = k.max(1);
+    if words.len() <= k {
+        return vec![words.join(" ")];
+    }
+    words.windows(k).map(|window| window.join(" ")).collect()
+}
+
+/// Contiguous k-grams of characters, with runs of whitespace collapsed.
+/// Texts shorter than k yield a single shingle.
+pub fn char_shingles(text: &str, k: usize) -> Vec<String> {
+    let chars: Vec<char> = text.split_whitespace().collect::<Vec<_>>().join(" ").chars().collect();
+    if chars.is_empty() {
+        return Vec::new();
+    }
+    let k = k.max(1);
+    if chars.len() <= k {
+        return vec![chars.iter().collect()];
+    }
+    chars.windows(k).map(|window| window.iter().collect()).collect()
+}
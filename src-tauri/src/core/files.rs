@@ -0,0 +1,767 @@
+//! Directory scans: walking a folder tree with glob and size filters, grouping
+//! byte-identical files, and finding near-duplicate text files.
+//!
+//! Candidates are narrowed in three passes so most files are never read in
+//! full: equal sizes, then a BLAKE3 hash of the first bytes, then the full
+//! BLAKE3 hash. Actions on the groups (delete, hardlink, quarantine) keep the
+//! first path of each group and must be confirmed unless they are a dry run.
+//! Near-duplicates run the regular pipeline with one whole-text document per file.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use memmap2::Mmap;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::core::engine::DeduplicationEngine;
+use crate::error::{AppError, ErrorCode};
+use crate::state::{DedupStats, DedupStrategySettings, SimilarityMethod, SplitStrategy};
+
+/// Bytes hashed by the partial-hash pass
+pub const PARTIAL_HASH_BYTES: usize = 16 * 1024;
+
+/// Which files of a folder tree to consider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanOptions {
+    pub root: String,
+    #[serde(default)]
+    pub include: Vec<String>, // Globs over paths relative to the root; empty includes everything
+    #[serde(default)]
+    pub exclude: Vec<String>, // Matching directories are not descended into
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    #[serde(default)]
+    pub follow_links: bool,
+}
+
+/// A file that passed the filters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScannedFile {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// A file left out of a scan or an action, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+fn glob_set(patterns: &[String]) -> Result<Option<GlobSet>, AppError> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| AppError::new(ErrorCode::InvalidInput, format!("Invalid glob '{}': {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| AppError::new(ErrorCode::InvalidInput, format!("Invalid globs: {}", e)))
+}
+
+/// Walks the tree under `options.root`, returning the matching files sorted by path
+/// and the entries that could not be read
+pub fn walk(options: &ScanOptions) -> Result<(Vec<ScannedFile>, Vec<SkippedFile>), AppError> {
+    let root = Path::new(&options.root);
+    if !root.is_dir() {
+        return Err(AppError::not_found("Directory", &options.root));
+    }
+    let include = glob_set(&options.include)?;
+    let exclude = glob_set(&options.exclude)?;
+    let excluded = |path: &Path| {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        exclude.as_ref().is_some_and(|set| set.is_match(relative))
+    };
+
+    let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let entries = WalkDir::new(root)
+        .follow_links(options.follow_links)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| entry.depth() == 0 || !excluded(entry.path()));
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                let path = e.path().map(|p| p.display().to_string()).unwrap_or_default();
+                skipped.push(SkippedFile { path, reason: e.to_string() });
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        if include.as_ref().is_some_and(|set| !set.is_match(relative)) {
+            continue;
+        }
+        let size = match entry.metadata() {
+            Ok(metadata) => metadata.len(),
+            Err(e) => {
+                skipped.push(SkippedFile { path: entry.path().display().to_string(), reason: e.to_string() });
+                continue;
+            }
+        };
+        if options.min_size.is_some_and(|min| size < min) || options.max_size.is_some_and(|max| size > max) {
+            continue;
+        }
+        files.push(ScannedFile { path: entry.into_path(), size });
+    }
+    Ok((files, skipped))
+}
+
+// ---------------------------------------------------------------------
+// Identical files
+// ---------------------------------------------------------------------
+
+/// Files with identical bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileGroup {
+    pub hash: String, // Hex BLAKE3 of the content
+    pub size: u64,
+    pub paths: Vec<String>, // Sorted; actions keep the first
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileScanStats {
+    pub files_scanned: usize,
+    pub bytes_scanned: u64,
+    pub same_size_files: usize,    // Left after the size pass
+    pub same_partial_files: usize, // Left after the partial-hash pass
+    pub duplicate_files: usize,    // Files beyond the first of each group
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileScanResults {
+    pub groups: Vec<FileGroup>, // Most reclaimable first
+    pub skipped: Vec<SkippedFile>,
+    pub stats: FileScanStats,
+}
+
+/// BLAKE3 of the first `PARTIAL_HASH_BYTES` of a file
+fn partial_hash(path: &Path) -> std::io::Result<String> {
+    let mut buffer = Vec::with_capacity(PARTIAL_HASH_BYTES);
+    File::open(path)?.take(PARTIAL_HASH_BYTES as u64).read_to_end(&mut buffer)?;
+    Ok(blake3::hash(&buffer).to_hex().to_string())
+}
+
+/// BLAKE3 of a whole file, memory-mapped
+fn full_hash(path: &Path) -> std::io::Result<String> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(blake3::hash(&[]).to_hex().to_string());
+    }
+    // SAFETY: the map is read-only and dropped before returning; a file truncated
+    // concurrently by another process can fault, as with any mmap-based hasher
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(blake3::hash(&map).to_hex().to_string())
+}
+
+/// Splits every bucket of two or more files by a key, dropping files that fail and singletons
+fn refine<K: std::hash::Hash + Eq>(
+    buckets: Vec<Vec<ScannedFile>>,
+    skipped: &mut Vec<SkippedFile>,
+    key: impl Fn(&ScannedFile) -> std::io::Result<K>,
+) -> Vec<(K, Vec<ScannedFile>)> {
+    let mut refined = Vec::new();
+    for bucket in buckets {
+        let mut by_key: HashMap<K, Vec<ScannedFile>> = HashMap::new();
+        for file in bucket {
+            match key(&file) {
+                Ok(k) => by_key.entry(k).or_default().push(file),
+                Err(e) => skipped.push(SkippedFile { path: file.path.display().to_string(), reason: e.to_string() }),
+            }
+        }
+        refined.extend(by_key.into_iter().filter(|(_, files)| files.len() > 1));
+    }
+    refined
+}
+
+/// Groups the byte-identical files under `options.root`. Empty files are left out:
+/// they are all identical and free nothing. Hard links to one file count once, by
+/// their first path, since removing one of them frees nothing.
+pub fn find_identical_files(options: &ScanOptions) -> Result<FileScanResults, AppError> {
+    let (mut files, mut skipped) = walk(options)?;
+    let mut ids: HashSet<(u64, u64)> = HashSet::new();
+    files.retain(|file| {
+        let id = fs::metadata(&file.path).ok().and_then(|metadata| file_id(&metadata));
+        id.is_none_or(|id| ids.insert(id))
+    });
+    let mut stats = FileScanStats {
+        files_scanned: files.len(),
+        bytes_scanned: files.iter().map(|file| file.size).sum(),
+        ..FileScanStats::default()
+    };
+
+    let mut by_size: HashMap<u64, Vec<ScannedFile>> = HashMap::new();
+    for file in files.into_iter().filter(|file| file.size > 0) {
+        by_size.entry(file.size).or_default().push(file);
+    }
+    let sized: Vec<Vec<ScannedFile>> = by_size.into_values().filter(|files| files.len() > 1).collect();
+    stats.same_size_files = sized.iter().map(Vec::len).sum();
+
+    let partial = refine(sized, &mut skipped, |file| partial_hash(&file.path));
+    stats.same_partial_files = partial.iter().map(|(_, files)| files.len()).sum();
+
+    // Files no longer than the partial window are already fully hashed, so their partial key is the content hash
+    let (hashed, larger): (Vec<_>, Vec<_>) =
+        partial.into_iter().partition(|(_, files)| files[0].size <= PARTIAL_HASH_BYTES as u64);
+    let larger = larger.into_iter().map(|(_, files)| files).collect();
+    let full = hashed.into_iter().chain(refine(larger, &mut skipped, |file| full_hash(&file.path)));
+
+    let mut groups: Vec<FileGroup> = full
+        .into_iter()
+        .map(|(hash, files)| {
+            let size = files[0].size;
+            let mut paths: Vec<String> = files.iter().map(|file| file.path.display().to_string()).collect();
+            paths.sort();
+            FileGroup { hash, size, reclaimable_bytes: size * (paths.len() as u64 - 1), paths }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.reclaimable_bytes.cmp(&a.reclaimable_bytes).then_with(|| a.paths.cmp(&b.paths)));
+
+    stats.duplicate_files = groups.iter().map(|group| group.paths.len() - 1).sum();
+    stats.reclaimable_bytes = groups.iter().map(|group| group.reclaimable_bytes).sum();
+    Ok(FileScanResults { groups, skipped, stats })
+}
+
+// ---------------------------------------------------------------------
+// Actions
+// ---------------------------------------------------------------------
+
+/// What to do with the duplicates of each group (the first path is kept)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileAction {
+    Delete,
+    Hardlink,                        // Replace each duplicate by a hard link to the kept file
+    Quarantine { directory: String }, // Move duplicates into a folder for later review
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileActionRequest {
+    pub action: FileAction,
+    pub groups: Vec<FileGroup>,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub confirmed: bool, // Required for anything but a dry run
+}
+
+/// What happened (or would happen, in a dry run) to one duplicate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionOutcome {
+    pub path: String,
+    pub target: Option<String>, // Kept file for hardlinks, destination for quarantine
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileActionReport {
+    pub dry_run: bool,
+    pub applied: Vec<ActionOutcome>,
+    pub skipped: Vec<SkippedFile>, // Changed since the scan or failed
+    pub reclaimed_bytes: u64,
+}
+
+/// Applies an action to scanned groups. Every duplicate is re-hashed first and
+/// left alone if its content no longer matches the group. Symbolic links, paths
+/// listed more than once and hard links to the kept file are skipped, so an
+/// action never removes the only copy of the content.
+pub fn apply_action(request: &FileActionRequest) -> Result<FileActionReport, AppError> {
+    if !request.dry_run && !request.confirmed {
+        return Err(AppError::new(
+            ErrorCode::InvalidInput,
+            "File actions change the disk and must be confirmed; set confirmed, or dry_run to preview",
+        ));
+    }
+    if let FileAction::Quarantine { directory } = &request.action {
+        if !request.dry_run {
+            fs::create_dir_all(directory)
+                .map_err(|e| AppError::storage(Some(directory.clone()), "create directory", e.to_string()))?;
+        }
+    }
+
+    let mut report = FileActionReport {
+        dry_run: request.dry_run,
+        applied: Vec::new(),
+        skipped: Vec::new(),
+        reclaimed_bytes: 0,
+    };
+    // Canonical paths already kept or acted on, across groups
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    // Quarantine destinations handed out, which a dry run does not create
+    let mut destinations: HashSet<PathBuf> = HashSet::new();
+    for group in &request.groups {
+        let Some((kept, duplicates)) = group.paths.split_first() else {
+            continue;
+        };
+        let kept_file = match resolve(kept) {
+            Ok(resolved) if full_hash(Path::new(kept)).ok().as_deref() == Some(group.hash.as_str()) => Ok(resolved),
+            Ok(_) => Err("changed since the scan".to_string()),
+            Err(reason) => Err(reason),
+        };
+        let (kept_path, kept_id) = match kept_file {
+            Ok(resolved) => resolved,
+            Err(reason) => {
+                for path in duplicates {
+                    report.skipped.push(SkippedFile { path: path.clone(), reason: format!("kept file {}: {}", kept, reason) });
+                }
+                continue;
+            }
+        };
+        seen.insert(kept_path);
+
+        for path in duplicates {
+            let (canonical, id) = match resolve(path) {
+                Ok(resolved) => resolved,
+                Err(reason) => {
+                    report.skipped.push(SkippedFile { path: path.clone(), reason });
+                    continue;
+                }
+            };
+            if id.is_some() && id == kept_id {
+                report.skipped.push(SkippedFile { path: path.clone(), reason: format!("same file as {}", kept) });
+                continue;
+            }
+            if !seen.insert(canonical) {
+                report.skipped.push(SkippedFile { path: path.clone(), reason: "listed more than once".to_string() });
+                continue;
+            }
+            if full_hash(Path::new(path)).ok().as_deref() != Some(group.hash.as_str()) {
+                report.skipped.push(SkippedFile { path: path.clone(), reason: "changed since the scan".to_string() });
+                continue;
+            }
+            let target = match &request.action {
+                FileAction::Delete => None,
+                FileAction::Hardlink => Some(kept.clone()),
+                FileAction::Quarantine { directory } => {
+                    Some(quarantine_path(Path::new(directory), &group.hash, path, &mut destinations))
+                }
+            };
+            let result = if request.dry_run { Ok(()) } else { perform(&request.action, kept, path, target.as_deref()) };
+            match result {
+                Ok(()) => {
+                    report.reclaimed_bytes += group.size;
+                    report.applied.push(ActionOutcome { path: path.clone(), target });
+                }
+                Err(e) => report.skipped.push(SkippedFile { path: path.clone(), reason: e.to_string() }),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Canonical path and (device, inode) of a file; symbolic links are refused
+fn resolve(path: &str) -> Result<(PathBuf, Option<(u64, u64)>), String> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
+    if metadata.file_type().is_symlink() {
+        return Err("symbolic link".to_string());
+    }
+    let canonical = fs::canonicalize(path).map_err(|e| e.to_string())?;
+    Ok((canonical, file_id(&metadata)))
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None // Canonical paths still catch repeated entries
+}
+
+/// Free destination in the quarantine folder: hash prefix, then the file name.
+/// Destinations already in `taken` are skipped too, and the new one is added.
+fn quarantine_path(directory: &Path, hash: &str, path: &str, taken: &mut HashSet<PathBuf>) -> String {
+    let name = Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let prefix = &hash[..hash.len().min(12)];
+    let mut destination = directory.join(format!("{}-{}", prefix, name));
+    let mut n = 1;
+    while destination.exists() || taken.contains(&destination) {
+        destination = directory.join(format!("{}-{}-{}", prefix, n, name));
+        n += 1;
+    }
+    let display = destination.display().to_string();
+    taken.insert(destination);
+    display
+}
+
+fn perform(action: &FileAction, kept: &str, path: &str, target: Option<&str>) -> std::io::Result<()> {
+    match action {
+        FileAction::Delete => fs::remove_file(path),
+        FileAction::Hardlink => {
+            // Link beside the duplicate, then swap it in, so a failure leaves the duplicate intact
+            let tmp = format!("{}.jdeduplix-link", path);
+            fs::hard_link(kept, &tmp)?;
+            fs::rename(&tmp, path).inspect_err(|_| {
+                let _ = fs::remove_file(&tmp);
+            })
+        }
+        FileAction::Quarantine { .. } => {
+            let destination = target.unwrap_or_default();
+            // Rename fails across file systems; fall back to copy and remove
+            fs::rename(path, destination).or_else(|_| fs::copy(path, destination).and_then(|_| fs::remove_file(path)))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------
+// Near-duplicate files
+// ---------------------------------------------------------------------
+
+/// Files larger than this are not read as text
+pub const MAX_TEXT_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Text content of a file: UTF-8 without a BOM, with tags stripped from markup files.
+/// Binary files (a NUL byte in the first 8 KiB) and other encodings are rejected.
+pub fn extract_text(path: &Path) -> Result<String, String> {
+    let size = fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_TEXT_BYTES {
+        return Err(format!("larger than {} bytes", MAX_TEXT_BYTES));
+    }
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    if bytes.iter().take(8192).any(|&b| b == 0) {
+        return Err("binary file".to_string());
+    }
+    let text = String::from_utf8(bytes).map_err(|_| "not UTF-8 text".to_string())?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+
+    let extension = path.extension().map(|e| e.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("html" | "htm" | "xml" | "xhtml" | "svg") => Ok(strip_tags(text)),
+        _ => Ok(text.to_string()),
+    }
+}
+
+/// Markup without tags, scripts and styles, with entities for the common characters decoded
+fn strip_tags(markup: &str) -> String {
+    static BLOCKS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?is)<script\b.*?</script>|<style\b.*?</style>|<!--.*?-->").unwrap());
+    static TAGS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<[^>]*>").unwrap());
+    let text = BLOCKS.replace_all(markup, " ");
+    let text = TAGS.replace_all(&text, " ");
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// A file similar to the first file of its group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarFile {
+    pub id: usize, // Text id of the file's content
+    pub path: String,
+    pub similarity: f64,
+}
+
+/// Near-duplicate files, keyed by the file the others were matched against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarFileGroup {
+    pub id: usize,
+    pub path: String,
+    pub similarity: f64, // Mean similarity of the members
+    pub members: Vec<SimilarFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarFileResults {
+    pub groups: Vec<SimilarFileGroup>,
+    pub skipped: Vec<SkippedFile>, // Unreadable, binary or non-UTF-8 files
+    pub stats: DedupStats,         // Items are files
+    #[serde(skip)]
+    pub texts: Vec<String>, // Content of every compared file, by id
+}
+
+impl SimilarFileResults {
+    /// Shifts every id by `offset`, once the texts are stored after `offset` others
+    pub fn offset_ids(&mut self, offset: usize) {
+        for group in &mut self.groups {
+            group.id += offset;
+            for member in &mut group.members {
+                member.id += offset;
+            }
+        }
+    }
+}
+
+/// Finds near-duplicate text files under `options.root`, running `strategy` with
+/// each file as one `SplitStrategy::WholeText` document. Ids are positions in
+/// `texts`. Only the fingerprint methods are accepted: they scale linearly with
+/// file size, while edit distances over whole files do not, exact copies are
+/// `find_identical_files`' job, and semantic embeddings are too slow for a folder scan.
+pub fn find_similar_files(options: &ScanOptions, strategy: &DedupStrategySettings) -> Result<SimilarFileResults, AppError> {
+    match strategy.similarity_method {
+        SimilarityMethod::Winnowing | SimilarityMethod::CodeClone => {}
+        ref method => {
+            return Err(AppError::invalid_strategy(
+                "similarity_method",
+                format!("{:?} is not supported for similar files", method),
+                Some("use Winnowing, or CodeClone for source code".to_string()),
+            ))
+        }
+    }
+    let (files, mut skipped) = walk(options)?;
+    let mut engine = DeduplicationEngine::new(DedupStrategySettings {
+        split_strategy: SplitStrategy::WholeText,
+        ..strategy.clone()
+    });
+
+    let mut paths = Vec::new();
+    let mut texts = Vec::new();
+    for file in files {
+        match extract_text(&file.path) {
+            Ok(text) => {
+                engine.ingest(text.clone());
+                texts.push(text);
+                paths.push(file.path.display().to_string());
+            }
+            Err(reason) => skipped.push(SkippedFile { path: file.path.display().to_string(), reason }),
+        }
+    }
+
+    let results = engine.run()?;
+    let groups = results
+        .duplicate_groups
+        .into_iter()
+        .map(|group| SimilarFileGroup {
+            id: group.original_id,
+            path: paths[group.original_id].clone(),
+            similarity: group.similarity,
+            members: group
+                .members
+                .iter()
+                .map(|member| SimilarFile { id: member.id, path: paths[member.id].clone(), similarity: member.similarity })
+                .collect(),
+        })
+        .collect();
+    Ok(SimilarFileResults { groups, skipped, stats: results.stats, texts })
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(files: &[(&str, Vec<u8>)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("jdeduplix-files-{}", uuid::Uuid::new_v4()));
+        for (name, content) in files {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn test_identical_files_grouped() {
+        let big = vec![7u8; PARTIAL_HASH_BYTES + 10];
+        let mut big_tail = big.clone();
+        *big_tail.last_mut().unwrap() = 8; // Same size and prefix, different content
+        let root = scratch_dir(&[
+            ("a.txt", b"hello world".to_vec()),
+            ("docs/copy.txt", b"hello world".to_vec()),
+            ("docs/other.txt", b"hello there".to_vec()),
+            ("skip/a.txt", b"hello world".to_vec()),
+            ("big.bin", big.clone()),
+            ("big-copy.bin", big),
+            ("big-tail.bin", big_tail),
+            ("empty1", Vec::new()),
+            ("empty2", Vec::new()),
+        ]);
+        let options = ScanOptions {
+            root: root.display().to_string(),
+            exclude: vec!["skip".to_string()],
+            ..ScanOptions::default()
+        };
+
+        let results = find_identical_files(&options).unwrap();
+        assert_eq!(results.stats.files_scanned, 8);
+        assert_eq!(results.stats.same_size_files, 6);
+        assert_eq!(results.groups.len(), 2);
+        assert_eq!(results.groups[0].size, PARTIAL_HASH_BYTES as u64 + 10);
+        assert_eq!(results.groups[0].paths.len(), 2);
+        assert_eq!(results.groups[1].paths, vec![
+            root.join("a.txt").display().to_string(),
+            root.join("docs/copy.txt").display().to_string(),
+        ]);
+        assert_eq!(results.stats.reclaimable_bytes, PARTIAL_HASH_BYTES as u64 + 10 + 11);
+
+        let filtered = ScanOptions { include: vec!["*.txt".to_string()], max_size: Some(100), ..options };
+        let results = find_identical_files(&filtered).unwrap();
+        assert_eq!(results.groups.len(), 1);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_similar_files_grouped_by_path() {
+        let draft = "The quarterly report shows revenue growth across all regions, driven by strong demand.";
+        let root = scratch_dir(&[
+            ("notes/draft-v1.txt", draft.as_bytes().to_vec()),
+            ("notes/draft-v2.html", format!("<html><body><p>{}</p></body></html>", draft.replace("strong", "robust")).into_bytes()),
+            ("notes/shopping.txt", b"Milk, eggs, bread and coffee beans for the week".to_vec()),
+            ("image.bin", vec![0u8, 159, 146, 150]),
+        ]);
+        let strategy = DedupStrategySettings {
+            similarity_method: SimilarityMethod::Winnowing,
+            similarity_threshold: 0.6,
+            use_parallel: Some(false),
+            ..DedupStrategySettings::default()
+        };
+
+        let options = ScanOptions { root: root.display().to_string(), ..ScanOptions::default() };
+        for method in [SimilarityMethod::Semantic, SimilarityMethod::Levenshtein] {
+            let unsupported = DedupStrategySettings { similarity_method: method, ..strategy.clone() };
+            assert!(find_similar_files(&options, &unsupported).is_err());
+        }
+        let results = find_similar_files(&options, &strategy).unwrap();
+        assert_eq!(results.stats.total_items, 3);
+        assert_eq!(results.skipped.len(), 1);
+        assert_eq!(results.groups.len(), 1);
+        let group = &results.groups[0];
+        assert_eq!(group.path, root.join("notes/draft-v1.txt").display().to_string());
+        assert_eq!(group.members[0].path, root.join("notes/draft-v2.html").display().to_string());
+        assert!(group.members[0].similarity >= 0.6);
+        assert_eq!(results.texts[group.id], draft);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_actions_require_confirmation() {
+        let root = scratch_dir(&[("a.txt", b"same".to_vec()), ("b.txt", b"same".to_vec()), ("c.txt", b"same".to_vec())]);
+        let groups = find_identical_files(&ScanOptions { root: root.display().to_string(), ..ScanOptions::default() })
+            .unwrap()
+            .groups;
+        let quarantine = root.join("quarantine").display().to_string();
+        let mut request = FileActionRequest {
+            action: FileAction::Quarantine { directory: quarantine.clone() },
+            groups,
+            dry_run: false,
+            confirmed: false,
+        };
+        assert!(apply_action(&request).is_err());
+
+        request.dry_run = true;
+        let preview = apply_action(&request).unwrap();
+        assert_eq!(preview.applied.len(), 2);
+        assert!(root.join("b.txt").exists());
+
+        request.dry_run = false;
+        request.confirmed = true;
+        fs::write(root.join("c.txt"), b"edited").unwrap();
+        let report = apply_action(&request).unwrap();
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.reclaimed_bytes, 4);
+        assert!(root.join("a.txt").exists() && !root.join("b.txt").exists());
+        assert!(Path::new(report.applied[0].target.as_deref().unwrap()).starts_with(&quarantine));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_dry_run_quarantine_targets_are_unique() {
+        let root = scratch_dir(&[("x/a.txt", b"same".to_vec()), ("y/a.txt", b"same".to_vec()), ("z/a.txt", b"same".to_vec())]);
+        let groups = find_identical_files(&ScanOptions { root: root.display().to_string(), ..ScanOptions::default() })
+            .unwrap()
+            .groups;
+        let request = FileActionRequest {
+            action: FileAction::Quarantine { directory: root.join("quarantine").display().to_string() },
+            groups,
+            dry_run: true,
+            confirmed: false,
+        };
+        let preview = apply_action(&request).unwrap();
+        let targets: HashSet<_> = preview.applied.iter().map(|outcome| outcome.target.clone()).collect();
+        assert_eq!((preview.applied.len(), targets.len()), (2, 2));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    fn delete_request(paths: Vec<PathBuf>) -> FileActionRequest {
+        let hash = full_hash(&paths[0]).unwrap();
+        FileActionRequest {
+            action: FileAction::Delete,
+            groups: vec![FileGroup {
+                hash,
+                size: 4,
+                paths: paths.iter().map(|path| path.display().to_string()).collect(),
+                reclaimable_bytes: 0,
+            }],
+            dry_run: false,
+            confirmed: true,
+        }
+    }
+
+    #[test]
+    fn test_actions_skip_repeated_paths() {
+        let root = scratch_dir(&[("a.txt", b"same".to_vec()), ("b.txt", b"same".to_vec())]);
+        let (a, b) = (root.join("a.txt"), root.join("b.txt"));
+        // The kept file again under another spelling, and a duplicate listed twice
+        let report = apply_action(&delete_request(vec![a.clone(), root.join(".").join("a.txt"), b.clone(), b.clone()])).unwrap();
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.skipped.len(), 2);
+        assert!(a.exists() && !b.exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_actions_refuse_symlinks() {
+        let root = scratch_dir(&[("a.txt", b"same".to_vec())]);
+        let (a, link) = (root.join("a.txt"), root.join("link.txt"));
+        std::os::unix::fs::symlink(&a, &link).unwrap();
+
+        let report = apply_action(&delete_request(vec![link.clone(), a.clone()])).unwrap();
+        assert!(report.applied.is_empty());
+        assert!(report.skipped[0].reason.contains("symbolic link"));
+        let report = apply_action(&delete_request(vec![a.clone(), link.clone()])).unwrap();
+        assert!(report.applied.is_empty());
+        assert!(a.exists() && fs::symlink_metadata(&link).is_ok());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_actions_skip_hard_links_to_kept_file() {
+        let root = scratch_dir(&[("a.txt", b"same".to_vec())]);
+        let (a, linked) = (root.join("a.txt"), root.join("linked.txt"));
+        fs::hard_link(&a, &linked).unwrap();
+
+        let report = apply_action(&delete_request(vec![a.clone(), linked.clone()])).unwrap();
+        assert!(report.applied.is_empty());
+        assert!(report.skipped[0].reason.starts_with("same file as"));
+        assert!(a.exists() && linked.exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_count_once() {
+        let root = scratch_dir(&[("a.txt", b"same".to_vec()), ("b.txt", b"same".to_vec())]);
+        fs::hard_link(root.join("a.txt"), root.join("linked.txt")).unwrap();
+
+        let results = find_identical_files(&ScanOptions { root: root.display().to_string(), ..ScanOptions::default() }).unwrap();
+        assert_eq!(results.groups.len(), 1);
+        assert_eq!(results.groups[0].paths.len(), 2);
+        assert_eq!(results.stats.reclaimable_bytes, 4);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+//! Per-document language detection and the NLP resources available for each language.
+
+use rust_stemmers::{Algorithm, Stemmer};
+use serde::{Deserialize, Serialize};
+use stop_words::LANGUAGE as StopLanguage;
+use whatlang::Lang;
+
+/// Language assumed when detection is disabled or inconclusive.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Resources known for a language: stop word list and Snowball stemmer.
+#[derive(Debug)]
+pub struct LanguageSupport {
+    pub code: &'static str, // ISO 639-1
+    pub lang: Lang,
+    pub stop_words: Option<StopLanguage>,
+    pub stemmer: Option<Algorithm>,
+}
+
+/// Every language with stop words or a Snowball stemmer (all `rust_stemmers` algorithms).
+pub const LANGUAGES: &[LanguageSupport] = &[
+    LanguageSupport { code: "en", lang: Lang::Eng, stop_words: Some(StopLanguage::English), stemmer: Some(Algorithm::English) },
+    LanguageSupport { code: "fr", lang: Lang::Fra, stop_words: Some(StopLanguage::French), stemmer: Some(Algorithm::French) },
+    LanguageSupport { code: "es", lang: Lang::Spa, stop_words: Some(StopLanguage::Spanish), stemmer: Some(Algorithm::Spanish) },
+    LanguageSupport { code: "de", lang: Lang::Deu, stop_words: Some(StopLanguage::German), stemmer: Some(Algorithm::German) },
+    LanguageSupport { code: "it", lang: Lang::Ita, stop_words: Some(StopLanguage::Italian), stemmer: Some(Algorithm::Italian) },
+    LanguageSupport { code: "pt", lang: Lang::Por, stop_words: Some(StopLanguage::Portuguese), stemmer: Some(Algorithm::Portuguese) },
+    LanguageSupport { code: "ru", lang: Lang::Rus, stop_words: Some(StopLanguage::Russian), stemmer: Some(Algorithm::Russian) },
+    LanguageSupport { code: "ja", lang: Lang::Jpn, stop_words: Some(StopLanguage::Japanese), stemmer: None },
+    LanguageSupport { code: "zh", lang: Lang::Cmn, stop_words: Some(StopLanguage::Chinese), stemmer: None },
+    LanguageSupport { code: "ar", lang: Lang::Ara, stop_words: Some(StopLanguage::Arabic), stemmer: Some(Algorithm::Arabic) },
+    LanguageSupport { code: "hi", lang: Lang::Hin, stop_words: Some(StopLanguage::Hindi), stemmer: None },
+    LanguageSupport { code: "ko", lang: Lang::Kor, stop_words: Some(StopLanguage::Korean), stemmer: None },
+    LanguageSupport { code: "nl", lang: Lang::Nld, stop_words: Some(StopLanguage::Dutch), stemmer: Some(Algorithm::Dutch) },
+    LanguageSupport { code: "sv", lang: Lang::Swe, stop_words: Some(StopLanguage::Swedish), stemmer: Some(Algorithm::Swedish) },
+    LanguageSupport { code: "tr", lang: Lang::Tur, stop_words: Some(StopLanguage::Turkish), stemmer: Some(Algorithm::Turkish) },
+    LanguageSupport { code: "da", lang: Lang::Dan, stop_words: Some(StopLanguage::Danish), stemmer: Some(Algorithm::Danish) },
+    LanguageSupport { code: "fi", lang: Lang::Fin, stop_words: Some(StopLanguage::Finnish), stemmer: Some(Algorithm::Finnish) },
+    LanguageSupport { code: "el", lang: Lang::Ell, stop_words: Some(StopLanguage::Greek), stemmer: Some(Algorithm::Greek) },
+    LanguageSupport { code: "hu", lang: Lang::Hun, stop_words: Some(StopLanguage::Hungarian), stemmer: Some(Algorithm::Hungarian) },
+    LanguageSupport { code: "no", lang: Lang::Nob, stop_words: Some(StopLanguage::Norwegian), stemmer: Some(Algorithm::Norwegian) },
+    LanguageSupport { code: "ro", lang: Lang::Ron, stop_words: Some(StopLanguage::Romanian), stemmer: Some(Algorithm::Romanian) },
+    LanguageSupport { code: "ta", lang: Lang::Tam, stop_words: None, stemmer: Some(Algorithm::Tamil) },
+];
+
+/// Language detected for a document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectedLanguage {
+    /// ISO 639-1 code for supported languages, ISO 639-3 otherwise
+    pub code: String,
+    pub name: String,
+    pub confidence: f64,
+    pub reliable: bool,
+}
+
+/// Detects the language of a text.
+pub fn detect_language(text: &str) -> Option<DetectedLanguage> {
+    let info = whatlang::detect(text)?;
+    let lang = info.lang();
+    let code = support_for_lang(lang)
+        .map(|support| support.code)
+        .unwrap_or_else(|| lang.code());
+
+    Some(DetectedLanguage {
+        code: code.to_string(),
+        name: lang.eng_name().to_string(),
+        confidence: info.confidence(),
+        reliable: info.is_reliable(),
+    })
+}
+
+/// Looks up the resources for an ISO 639-1 code.
+pub fn support(code: &str) -> Option<&'static LanguageSupport> {
+    LANGUAGES.iter().find(|support| support.code == code)
+}
+
+/// Looks up the resources for a `whatlang` language.
+pub fn support_for_lang(lang: Lang) -> Option<&'static LanguageSupport> {
+    LANGUAGES.iter().find(|support| support.lang == lang)
+}
+
+/// Creates the Snowball stemmer for an ISO 639-1 code, if one exists.
+pub fn stemmer_for(code: &str) -> Option<Stemmer> {
+    support(code)
+        .and_then(|support| support.stemmer)
+        .map(Stemmer::create)
+}
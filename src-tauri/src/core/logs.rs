@@ -0,0 +1,317 @@
+//! Drain-style log template mining (He et al., 2017).
+//!
+//! Lines are routed through a fixed-depth tree, first by token count and then by
+//! their leading tokens, and matched against the templates of the leaf they
+//! reach by positional token similarity. Tokens that differ become wildcards.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::masking::{MaskRule, Masker};
+use crate::core::segmentation::segment_words;
+use crate::state::DedupStats;
+
+/// Template token standing for a variable parameter
+pub const WILDCARD: &str = "<*>";
+
+/// Log mode settings, stored in `DedupStrategySettings::log`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogStrategy {
+    /// Tree depth, counting the root, the token-count layer and the leaves. Drain's
+    /// `depth - 2` counts the nodes below the root, so `depth - 3` leading tokens route a line
+    #[serde(default = "default_depth")]
+    pub depth: usize,
+    /// Share of positions that must match a template for a line to join it
+    #[serde(default = "default_similarity_threshold")]
+    pub similarity_threshold: f64,
+    /// Children per tree node; further leading tokens share a wildcard branch
+    #[serde(default = "default_max_children")]
+    pub max_children: usize,
+    /// Example parameter lists kept per template
+    #[serde(default = "default_max_examples")]
+    pub max_examples: usize,
+    /// Apply the config's masking rules to each line first
+    #[serde(default = "default_apply_masks")]
+    pub apply_masks: bool,
+}
+
+fn default_depth() -> usize {
+    4
+}
+
+fn default_similarity_threshold() -> f64 {
+    0.4
+}
+
+fn default_max_children() -> usize {
+    100
+}
+
+fn default_max_examples() -> usize {
+    3
+}
+
+fn default_apply_masks() -> bool {
+    true
+}
+
+impl Default for LogStrategy {
+    fn default() -> Self {
+        Self {
+            depth: default_depth(),
+            similarity_threshold: default_similarity_threshold(),
+            max_children: default_max_children(),
+            max_examples: default_max_examples(),
+            apply_masks: default_apply_masks(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------
+// Results
+// ---------------------------------------------------------------------
+
+/// A line of an ingested text (lines are numbered from 1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogLocation {
+    pub text_id: usize,
+    pub line: usize,
+}
+
+/// A mined template and the lines it covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogTemplate {
+    pub template: String, // Tokens joined by spaces, parameters as `<*>`
+    pub count: usize,
+    pub first: LogLocation,
+    pub last: LogLocation,
+    pub examples: Vec<Vec<String>>, // Parameter values of a few lines, in wildcard order
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogResults {
+    pub templates: Vec<LogTemplate>, // Most frequent first
+    pub stats: DedupStats,           // Items are lines; unique items are templates
+}
+
+// ---------------------------------------------------------------------
+// Parse tree
+// ---------------------------------------------------------------------
+
+struct Cluster {
+    tokens: Vec<String>,
+    count: usize,
+    first: LogLocation,
+    last: LogLocation,
+    examples: Vec<Vec<String>>, // Tokens of example lines
+}
+
+#[derive(Default)]
+struct Node {
+    children: HashMap<String, Node>,
+    clusters: Vec<usize>, // Leaves only
+}
+
+/// Incremental template miner; feed it lines, then collect the templates
+pub struct LogMiner {
+    strategy: LogStrategy,
+    masker: Option<Masker>,
+    root: HashMap<usize, Node>, // By token count
+    clusters: Vec<Cluster>,
+    lines: usize,
+    comparisons: usize,
+}
+
+impl LogMiner {
+    pub fn new(strategy: &LogStrategy, masks: &[MaskRule]) -> Self {
+        Self {
+            masker: if strategy.apply_masks { Masker::new(masks) } else { None },
+            strategy: strategy.clone(),
+            root: HashMap::new(),
+            clusters: Vec::new(),
+            lines: 0,
+            comparisons: 0,
+        }
+    }
+
+    /// Adds every line of a text; `add_line` skips lines without tokens
+    pub fn add_text(&mut self, text_id: usize, text: &str) {
+        for (idx, line) in text.lines().enumerate() {
+            self.add_line(line, LogLocation { text_id, line: idx + 1 });
+        }
+    }
+
+    pub fn add_line(&mut self, line: &str, location: LogLocation) {
+        let masked = match &self.masker {
+            Some(masker) => masker.apply(line).0,
+            None => line.to_string(),
+        };
+        let tokens: Vec<String> = segment_words(&masked).into_iter().map(str::to_string).collect();
+        if tokens.is_empty() {
+            return;
+        }
+        self.lines += 1;
+
+        let leaf = self.leaf(&tokens);
+        let candidates = leaf.clusters.clone();
+        let threshold = self.strategy.similarity_threshold;
+        self.comparisons += candidates.len();
+        let best = candidates
+            .into_iter()
+            .map(|idx| (idx, similarity(&self.clusters[idx].tokens, &tokens)))
+            .filter(|&(_, (score, _))| score >= threshold)
+            // Highest similarity, then the template with the most wildcards
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(idx, _)| idx);
+
+        match best {
+            Some(idx) => {
+                let max_examples = self.strategy.max_examples;
+                let cluster = &mut self.clusters[idx];
+                for (template, token) in cluster.tokens.iter_mut().zip(&tokens) {
+                    if template != token {
+                        *template = WILDCARD.to_string();
+                    }
+                }
+                cluster.count += 1;
+                cluster.last = location;
+                if cluster.examples.len() < max_examples && !cluster.examples.contains(&tokens) {
+                    cluster.examples.push(tokens);
+                }
+            }
+            None => {
+                let idx = self.clusters.len();
+                self.clusters.push(Cluster {
+                    examples: if self.strategy.max_examples > 0 { vec![tokens.clone()] } else { Vec::new() },
+                    tokens: tokens.clone(),
+                    count: 1,
+                    first: location,
+                    last: location,
+                });
+                self.leaf(&tokens).clusters.push(idx);
+            }
+        }
+    }
+
+    /// Leaf for a line: token count, then up to `depth - 3` leading tokens (see `LogStrategy::depth`)
+    fn leaf(&mut self, tokens: &[String]) -> &mut Node {
+        let max_children = self.strategy.max_children;
+        let mut node = self.root.entry(tokens.len()).or_default();
+        for token in tokens.iter().take(self.strategy.depth.saturating_sub(3)) {
+            // Tokens with digits are likely parameters and share the wildcard branch
+            let full = !node.children.contains_key(token) && node.children.len() >= max_children;
+            let key = if full || token.chars().any(|c| c.is_ascii_digit()) {
+                WILDCARD
+            } else {
+                token.as_str()
+            };
+            node = node.children.entry(key.to_string()).or_default();
+        }
+        node
+    }
+
+    pub fn finish(self) -> LogResults {
+        let mut templates: Vec<LogTemplate> = self
+            .clusters
+            .into_iter()
+            .map(|cluster| {
+                let parameters = |line: &Vec<String>| -> Vec<String> {
+                    cluster
+                        .tokens
+                        .iter()
+                        .zip(line)
+                        .filter(|(template, _)| *template == WILDCARD)
+                        .map(|(_, token)| token.clone())
+                        .collect()
+                };
+                LogTemplate {
+                    template: cluster.tokens.join(" "),
+                    count: cluster.count,
+                    first: cluster.first,
+                    last: cluster.last,
+                    examples: cluster.examples.iter().map(parameters).collect(),
+                }
+            })
+            .collect();
+        templates.sort_by(|a, b| {
+            b.count.cmp(&a.count).then((a.first.text_id, a.first.line).cmp(&(b.first.text_id, b.first.line)))
+        });
+
+        let repeated = templates.iter().filter(|template| template.count > 1).count();
+        LogResults {
+            stats: DedupStats {
+                total_items: self.lines,
+                unique_items: templates.len(),
+                duplicate_groups: repeated,
+                comparisons: self.comparisons,
+                pruned_comparisons: 0,
+            },
+            templates,
+        }
+    }
+}
+
+/// Share of positions where the line equals the template, and the template's wildcard count
+fn similarity(template: &[String], tokens: &[String]) -> (f64, usize) {
+    let mut same = 0;
+    let mut wildcards = 0;
+    for (t, token) in template.iter().zip(tokens) {
+        if t == WILDCARD {
+            wildcards += 1;
+        } else if t == token {
+            same += 1;
+        }
+    }
+    (same as f64 / template.len().max(1) as f64, wildcards)
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines_grouped_by_template() {
+        let mut miner = LogMiner::new(&LogStrategy::default(), &[]);
+        miner.add_text(
+            0,
+            "Connected to 10.0.0.1 in 35 ms\n\
+             User alice logged in\n\
+             Connected to 10.0.0.7 in 12 ms\n\n\
+             User bob logged in",
+        );
+        miner.add_text(1, "Connected to 10.0.0.9 in 40 ms\nDisk full on /dev/sda1");
+        let results = miner.finish();
+
+        assert_eq!(results.stats.total_items, 6);
+        assert_eq!(results.templates.len(), 3);
+        let connected = &results.templates[0];
+        assert_eq!(connected.template, "Connected to <*> in <*> ms");
+        assert_eq!(connected.count, 3);
+        assert_eq!(connected.first, LogLocation { text_id: 0, line: 1 });
+        assert_eq!(connected.last, LogLocation { text_id: 1, line: 1 });
+        assert_eq!(connected.examples[0], vec!["10.0.0.1".to_string(), "35".to_string()]);
+
+        assert_eq!(results.templates[1].template, "User <*> logged in");
+        assert_eq!(results.templates[1].last.line, 5);
+        assert_eq!(results.templates[2].count, 1);
+    }
+
+    #[test]
+    fn test_masks_apply_before_mining() {
+        let masks = vec![MaskRule {
+            name: "NUM".to_string(),
+            pattern: r"\b\d+\b".to_string(),
+            replacement: "<NUM>".to_string(),
+        }];
+        let mut miner = LogMiner::new(&LogStrategy::default(), &masks);
+        miner.add_text(0, "retry 1 of 3\nretry 2 of 3");
+        let results = miner.finish();
+        assert_eq!(results.templates[0].template, "retry <NUM> of <NUM>");
+        assert!(results.templates[0].examples[0].is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+//! Regex masking rules: volatile tokens (ids, timestamps, addresses) replaced by placeholders.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A named regex whose matches are replaced, e.g. UUIDs by `<UUID>`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaskRule {
+    pub name: String,
+    pub pattern: String,
+    pub replacement: String, // May refer to capture groups ($1, ${name})
+}
+
+impl MaskRule {
+    fn new(name: &str, pattern: &str, replacement: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+        }
+    }
+
+    pub fn compile(&self) -> Result<Regex, String> {
+        Regex::new(&self.pattern).map_err(|e| format!("Invalid pattern for mask '{}': {}", self.name, e))
+    }
+}
+
+/// Built-in masks, in the order they should run (more specific tokens first)
+pub fn builtin_masks() -> Vec<MaskRule> {
+    // HEX: a 0x prefix, or six or more hex digits mixing digits and letters, so words
+    // ("decade"), plain numbers and short tokens ("b2b", "3d") are left alone. Without
+    // lookahead, the length is spelled out by where the first digit/letter switch falls.
+    let mixed: Vec<String> = [("[0-9]", "[a-f]"), ("[a-f]", "[0-9]")]
+        .into_iter()
+        .flat_map(|(run, switch)| {
+            (1..=5).map(move |len| match len {
+                5 => format!("{}{{5,}}{}", run, switch),
+                _ => format!("{}{{{}}}{}[0-9a-f]{{{}}}", run, len, switch, 5 - len),
+            })
+        })
+        .collect();
+    let hex = format!(r"(?i)\b0x[0-9a-f]+\b|\b(?:{})[0-9a-f]*\b", mixed.join("|"));
+
+    vec![
+        MaskRule::new(
+            "UUID",
+            r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b",
+            "<UUID>",
+        ),
+        MaskRule::new(
+            "TIMESTAMP",
+            r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(?::\d{2}(?:[.,]\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?|\b\d{2}:\d{2}:\d{2}(?:[.,]\d+)?\b",
+            "<TIMESTAMP>",
+        ),
+        MaskRule::new(
+            "IP",
+            r"\b(?:\d{1,3}\.){3}\d{1,3}(?::\d{1,5})?\b|(?i)\b(?:[0-9a-f]{1,4}:){7}[0-9a-f]{1,4}\b",
+            "<IP>",
+        ),
+        MaskRule::new("HEX", &hex, "<HEX>"),
+        MaskRule::new("NUM", r"\b\d+(?:[.,]\d+)*\b", "<NUM>"),
+    ]
+}
+
+/// How often a mask matched in a text
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaskHit {
+    pub rule: String,
+    pub count: usize,
+}
+
+/// Compiled mask rules, applied in order
+pub struct Masker {
+    rules: Vec<(MaskRule, Regex)>,
+}
+
+impl Masker {
+    /// Masker for the rules that compile (strategy validation reports the others), None without any
+    pub fn new(rules: &[MaskRule]) -> Option<Self> {
+        let rules: Vec<(MaskRule, Regex)> = rules
+            .iter()
+            .filter_map(|rule| rule.compile().ok().map(|regex| (rule.clone(), regex)))
+            .collect();
+        (!rules.is_empty()).then_some(Self { rules })
+    }
+
+    /// Masked text and the rules that fired
+    pub fn apply(&self, text: &str) -> (String, Vec<MaskHit>) {
+        let mut masked = text.to_string();
+        let mut hits = Vec::new();
+        for (rule, regex) in &self.rules {
+            let count = regex.find_iter(&masked).count();
+            if count > 0 {
+                masked = regex.replace_all(&masked, rule.replacement.as_str()).into_owned();
+                hits.push(MaskHit { rule: rule.name.clone(), count });
+            }
+        }
+        (masked, hits)
+    }
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_masks() {
+        let masker = Masker::new(&builtin_masks()).unwrap();
+        let (masked, hits) = masker.apply(
+            "2024-03-01T10:15:00Z req 3f2a9c1e-0b4d-4c6e-9f8a-7b6c5d4e3f2a from 10.0.0.12:8080 \
+             failed at 0x7ffe12 (code 500, attempt 3) in module cafe",
+        );
+        assert_eq!(
+            masked,
+            "<TIMESTAMP> req <UUID> from <IP> failed at <HEX> (code <NUM>, attempt <NUM>) in module cafe"
+        );
+        let fired: Vec<(&str, usize)> = hits.iter().map(|hit| (hit.rule.as_str(), hit.count)).collect();
+        assert_eq!(fired, vec![("UUID", 1), ("TIMESTAMP", 1), ("IP", 1), ("HEX", 1), ("NUM", 2)]);
+    }
+
+    #[test]
+    fn test_hex_mask_needs_six_digits_or_prefix() {
+        let masker = Masker::new(&builtin_masks()).unwrap();
+        let (masked, _) = masker.apply("b2b a4 3d 3f2a9c e1a4b0d7 7e57ab 0xff decade 123456 ab12c");
+        assert_eq!(masked, "b2b a4 3d <HEX> <HEX> <HEX> <HEX> decade <NUM> ab12c");
+    }
+
+    #[test]
+    fn test_custom_rule_with_capture_group() {
+        let rule = MaskRule::new("ORDER", r"(order|invoice) #\d+", "$1 <ORDER>");
+        let (masked, hits) = Masker::new(&[rule]).unwrap().apply("Refund for order #84321, see invoice #12");
+        assert_eq!(masked, "Refund for order <ORDER>, see invoice <ORDER>");
+        assert_eq!(hits[0].count, 2);
+        assert!(MaskRule::new("broken", "(", "x").compile().is_err());
+        assert!(Masker::new(&[]).is_none());
+    }
+}
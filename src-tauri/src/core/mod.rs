@@ -1,15 +1,23 @@
 // Core deduplication functionality
 
+pub mod blocking;
 pub mod classifier;
+pub mod code;
+pub mod diff;
+pub mod segmentation;
 pub mod semantic;
 pub mod engine;
+pub mod explain;
+pub mod features;
+pub mod files;
+pub mod language;
+pub mod logs;
+pub mod masking;
+pub mod normalization;
+pub mod normalizers;
+pub mod records;
 pub mod storage;
+pub mod weighting;
+pub mod winnowing;
 
-/// Main deduplication core that coordinates all components
-pub struct DeduplicationCore;
-
-impl DeduplicationCore {
-    pub fn new() -> Self {
-        Self {}
-    }
-}
+pub use engine::DeduplicationEngine;
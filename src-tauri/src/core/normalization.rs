@@ -0,0 +1,256 @@
+//! Declarative normalization pipeline shared by the classifier and the semantic analyzer.
+//!
+//! A strategy either lists its steps in `normalization`, in the order they run,
+//! or leaves it unset and gets the default order built from its individual flags.
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::config::DynamicConfig;
+use crate::core::explain::NormalizationStepKind;
+use crate::core::language;
+use crate::core::masking::{MaskHit, MaskRule, Masker};
+use crate::core::segmentation::segment_words;
+use crate::core::normalizers::{DomainNormalizer, NormalizerPipeline};
+use crate::state::DedupStrategySettings;
+
+/// Unicode normalization form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UnicodeForm {
+    Nfc,
+    #[default]
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+/// A normalization step with its parameters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NormalizationRule {
+    Masking, // Masking rules of the dynamic config
+    Domain { normalizers: Vec<DomainNormalizer> },
+    Case,        // Lowercase
+    Whitespace,  // Collapse runs of whitespace into single spaces
+    Punctuation, // Remove ASCII punctuation
+    Unicode { form: UnicodeForm },
+    Stopwords, // Stop words of the document language, including user ones
+    Stemming,  // Snowball stemmer of the document language, when there is one
+    Encoding,  // Remove non-ASCII symbols
+}
+
+impl NormalizationRule {
+    pub fn kind(&self) -> NormalizationStepKind {
+        match self {
+            NormalizationRule::Masking => NormalizationStepKind::Masking,
+            NormalizationRule::Domain { .. } => NormalizationStepKind::Domain,
+            NormalizationRule::Case => NormalizationStepKind::Case,
+            NormalizationRule::Whitespace => NormalizationStepKind::Whitespace,
+            NormalizationRule::Punctuation => NormalizationStepKind::Punctuation,
+            NormalizationRule::Unicode { .. } => NormalizationStepKind::Unicode,
+            NormalizationRule::Stopwords => NormalizationStepKind::Stopwords,
+            NormalizationRule::Stemming => NormalizationStepKind::Stemming,
+            NormalizationRule::Encoding => NormalizationStepKind::Encoding,
+        }
+    }
+}
+
+/// One entry of `DedupStrategySettings::normalization`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineStep {
+    pub rule: NormalizationRule,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Steps of a strategy: its explicit list, or the default order driven by its flags
+pub fn strategy_steps(strategy: &DedupStrategySettings) -> Vec<PipelineStep> {
+    if let Some(steps) = &strategy.normalization {
+        return steps.clone();
+    }
+
+    let normalizers = strategy.domain_normalizers.clone().unwrap_or_default();
+    let step = |rule: NormalizationRule, enabled: bool| PipelineStep { rule, enabled };
+    vec![
+        step(NormalizationRule::Masking, true),
+        step(NormalizationRule::Domain { normalizers }, true),
+        step(NormalizationRule::Case, !strategy.case_sensitive.unwrap_or(true)),
+        step(NormalizationRule::Whitespace, strategy.ignore_whitespace.unwrap_or(false)),
+        step(NormalizationRule::Punctuation, strategy.ignore_punctuation.unwrap_or(false)),
+        step(
+            NormalizationRule::Unicode { form: UnicodeForm::Nfd },
+            strategy.normalize_unicode.unwrap_or(false),
+        ),
+        step(NormalizationRule::Stopwords, strategy.ignore_stopwords.unwrap_or(false)),
+        step(NormalizationRule::Stemming, strategy.stemming.unwrap_or(false)),
+        step(NormalizationRule::Encoding, strategy.encoding_normalization.unwrap_or(false)),
+    ]
+}
+
+/// Compiled normalization steps, in order
+pub struct NormalizationPipeline {
+    steps: Vec<(PipelineStep, Option<NormalizerPipeline>)>, // Domain steps carry their compiled normalizers
+    masker: Option<Masker>,
+}
+
+impl std::fmt::Debug for NormalizationPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let steps: Vec<&PipelineStep> = self.steps.iter().map(|(step, _)| step).collect();
+        f.debug_struct("NormalizationPipeline").field("steps", &steps).finish_non_exhaustive()
+    }
+}
+
+impl NormalizationPipeline {
+    pub fn new(steps: &[PipelineStep], masks: &[MaskRule]) -> Self {
+        let steps = steps
+            .iter()
+            .map(|step| {
+                let domain = match &step.rule {
+                    NormalizationRule::Domain { normalizers } => NormalizerPipeline::new(normalizers),
+                    _ => None,
+                };
+                (step.clone(), domain)
+            })
+            .collect();
+        Self { steps, masker: Masker::new(masks) }
+    }
+
+    pub fn for_strategy(strategy: &DedupStrategySettings) -> Self {
+        let masks = strategy.config.as_ref().map(|config| config.user_masking_rules.as_slice());
+        Self::new(&strategy_steps(strategy), masks.unwrap_or_default())
+    }
+
+    /// Normalize text, reporting each step's output (and whether it ran) to `record`
+    pub fn run(
+        &self,
+        text: &str,
+        lang_code: &str,
+        config: &DynamicConfig,
+        record: impl FnMut(NormalizationStepKind, bool, &str),
+    ) -> String {
+        self.run_with_masks(text, lang_code, config, record).0
+    }
+
+    /// Like [`Self::run`], also returning the masking rules that fired
+    pub fn run_with_masks(
+        &self,
+        text: &str,
+        lang_code: &str,
+        config: &DynamicConfig,
+        mut record: impl FnMut(NormalizationStepKind, bool, &str),
+    ) -> (String, Vec<MaskHit>) {
+        let mut normalized = text.to_string();
+        let mut hits = Vec::new();
+        for (step, domain) in &self.steps {
+            let output = if step.enabled {
+                self.apply(&step.rule, domain.as_ref(), &normalized, lang_code, config, &mut hits)
+            } else {
+                None
+            };
+            let applied = output.is_some();
+            if let Some(output) = output {
+                normalized = output;
+            }
+            record(step.rule.kind(), applied, &normalized);
+        }
+        (normalized, hits)
+    }
+
+    /// Output of one step, None when it has nothing to do (no masks, no normalizers, no stemmer)
+    fn apply(
+        &self,
+        rule: &NormalizationRule,
+        domain: Option<&NormalizerPipeline>,
+        text: &str,
+        lang_code: &str,
+        config: &DynamicConfig,
+        hits: &mut Vec<MaskHit>,
+    ) -> Option<String> {
+        Some(match rule {
+            NormalizationRule::Masking => {
+                let (masked, fired) = self.masker.as_ref()?.apply(text);
+                hits.extend(fired);
+                masked
+            }
+            NormalizationRule::Domain { .. } => domain?.apply(text),
+            NormalizationRule::Case => text.to_lowercase(),
+            NormalizationRule::Whitespace => text.split_whitespace().collect::<Vec<_>>().join(" "),
+            NormalizationRule::Punctuation => text.chars().filter(|c| !c.is_ascii_punctuation()).collect(),
+            NormalizationRule::Unicode { form } => match form {
+                UnicodeForm::Nfc => text.nfc().collect(),
+                UnicodeForm::Nfd => text.nfd().collect(),
+                UnicodeForm::Nfkc => text.nfkc().collect(),
+                UnicodeForm::Nfkd => text.nfkd().collect(),
+            },
+            NormalizationRule::Stopwords => {
+                let stop_words = config.merge_stop_words(lang_code);
+                // Script-aware words, so unspaced Chinese and Japanese text is filtered too
+                segment_words(text)
+                    .into_iter()
+                    .filter(|word| !stop_words.contains(&word.to_lowercase()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            NormalizationRule::Stemming => {
+                let stemmer = language::stemmer_for(lang_code)?;
+                text.split_whitespace()
+                    .map(|word| stemmer.stem(word).to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            NormalizationRule::Encoding => text.chars().filter(|c| c.is_ascii() || c.is_alphanumeric()).collect(),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_run_in_the_configured_order() {
+        let config = DynamicConfig::default();
+        let step = |rule| PipelineStep { rule, enabled: true };
+
+        // Stemming first turns the stop word "ourselves" into "ourselv", which is kept,
+        // and "running" into the stop word "run", which is dropped
+        let stem_first = NormalizationPipeline::new(&[step(NormalizationRule::Stemming), step(NormalizationRule::Stopwords)], &[]);
+        let stop_first = NormalizationPipeline::new(&[step(NormalizationRule::Stopwords), step(NormalizationRule::Stemming)], &[]);
+        assert_eq!(stop_first.run("ourselves running cats", "en", &config, |_, _, _| {}), "run cat");
+        assert_eq!(stem_first.run("ourselves running cats", "en", &config, |_, _, _| {}), "ourselv cat");
+
+        // Disabled steps are traced but leave the text unchanged
+        let mut trace = Vec::new();
+        let pipeline = NormalizationPipeline::new(&[
+            PipelineStep { rule: NormalizationRule::Case, enabled: false },
+            step(NormalizationRule::Unicode { form: UnicodeForm::Nfkc }),
+        ], &[]);
+        let output = pipeline.run("Ｃａｆé", "en", &config, |kind, applied, text| trace.push((kind, applied, text.to_string())));
+        assert_eq!(output, "Café");
+        assert_eq!(trace[0], (NormalizationStepKind::Case, false, "Ｃａｆé".to_string()));
+    }
+
+    #[test]
+    fn test_stopwords_removed_from_unspaced_text() {
+        let pipeline = NormalizationPipeline::new(&[PipelineStep { rule: NormalizationRule::Stopwords, enabled: true }], &[]);
+        let output = pipeline.run("我们喜欢的猫在跑", "zh", &DynamicConfig::default(), |_, _, _| {});
+        assert!(!output.contains('的'), "{}", output);
+        assert!(output.contains('猫'));
+    }
+
+    #[test]
+    fn test_default_steps_follow_strategy_flags() {
+        let strategy = DedupStrategySettings { stemming: Some(false), ..DedupStrategySettings::default() };
+        let steps = strategy_steps(&strategy);
+        assert_eq!(steps.len(), 9);
+        assert!(steps.iter().any(|step| step.rule == NormalizationRule::Stemming && !step.enabled));
+        assert_eq!(steps[2].rule.kind(), NormalizationStepKind::Case);
+    }
+}
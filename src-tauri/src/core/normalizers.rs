@@ -0,0 +1,359 @@
+//! Domain normalizers: canonical forms for URLs, emails, phone numbers, dates and numbers.
+//!
+//! Every normalizer contributes a pattern; the selected ones are combined into a
+//! single regex and applied in one pass, so the output of one normalizer is never
+//! matched again by another.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+/// Query parameters dropped from URLs, besides every `utm_*` parameter
+const TRACKING_PARAMS: &[&str] = &[
+    "gclid", "dclid", "fbclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_ga", "_hsenc", "_hsmi", "ref",
+];
+/// Email providers that ignore dots in the local part
+const DOTLESS_PROVIDERS: &[&str] = &["gmail.com"];
+
+const MONTHS: &str = r"(?:jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)";
+
+/// Built-in normalizers, selected per strategy in `DedupStrategySettings::domain_normalizers`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DomainNormalizer {
+    /// Lowercase scheme and host, no default port, no tracking parameters
+    Url,
+    /// Lowercase, no plus-tag, no dots for providers that ignore them
+    Email,
+    /// E.164; numbers without an international prefix need a default country code
+    Phone { default_country_code: Option<u16> },
+    /// ISO 8601 (YYYY-MM-DD); `day_first` reads 01/02/2024 as 1 February
+    Date { day_first: bool },
+    /// No thousands separators, leading zeros or trailing fraction zeros
+    Number,
+}
+
+impl DomainNormalizer {
+    /// Matching order when patterns overlap at the same position
+    fn priority(&self) -> u8 {
+        match self {
+            DomainNormalizer::Url => 0,
+            DomainNormalizer::Email => 1,
+            DomainNormalizer::Date { .. } => 2,
+            DomainNormalizer::Phone { .. } => 3,
+            DomainNormalizer::Number => 4,
+        }
+    }
+
+    fn build(&self) -> Box<dyn Normalizer> {
+        match self {
+            DomainNormalizer::Url => Box::new(UrlNormalizer),
+            DomainNormalizer::Email => Box::new(EmailNormalizer),
+            DomainNormalizer::Phone { default_country_code } => Box::new(PhoneNormalizer {
+                default_country_code: *default_country_code,
+            }),
+            DomainNormalizer::Date { day_first } => Box::new(DateNormalizer { day_first: *day_first }),
+            DomainNormalizer::Number => Box::new(NumberNormalizer),
+        }
+    }
+}
+
+/// A domain normalizer: a pattern for candidate tokens and their canonical form.
+pub trait Normalizer: Send + Sync {
+    /// Regex (without capturing groups) matching candidate tokens
+    fn pattern(&self) -> String;
+    /// Canonical form of a match, or None to leave it unchanged
+    fn canonicalize(&self, token: &str) -> Option<String>;
+}
+
+/// The selected normalizers, compiled into one regex
+pub struct NormalizerPipeline {
+    pattern: Regex,
+    normalizers: Vec<Box<dyn Normalizer>>,
+}
+
+impl NormalizerPipeline {
+    /// Pipeline for the selected normalizers, None when nothing is selected
+    pub fn new(selected: &[DomainNormalizer]) -> Option<Self> {
+        let mut selected: Vec<&DomainNormalizer> = selected.iter().collect();
+        selected.sort_by_key(|normalizer| normalizer.priority());
+        selected.dedup_by_key(|normalizer| normalizer.priority());
+        if selected.is_empty() {
+            return None;
+        }
+
+        let normalizers: Vec<Box<dyn Normalizer>> = selected.into_iter().map(DomainNormalizer::build).collect();
+        let pattern = normalizers
+            .iter()
+            .enumerate()
+            .map(|(idx, normalizer)| format!("(?P<n{}>{})", idx, normalizer.pattern()))
+            .collect::<Vec<_>>()
+            .join("|");
+        Some(Self {
+            pattern: Regex::new(&pattern).expect("built-in normalizer patterns are valid"),
+            normalizers,
+        })
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.pattern
+            .replace_all(text, |caps: &Captures| {
+                let token = &caps[0];
+                (0..self.normalizers.len())
+                    .find(|idx| caps.name(&format!("n{}", idx)).is_some())
+                    .and_then(|idx| self.normalizers[idx].canonicalize(token))
+                    .unwrap_or_else(|| token.to_string())
+            })
+            .into_owned()
+    }
+}
+
+// ---------------------------------------------------------------------
+// Normalizers
+// ---------------------------------------------------------------------
+
+struct UrlNormalizer;
+
+impl Normalizer for UrlNormalizer {
+    fn pattern(&self) -> String {
+        r#"(?i:\b(?:https?|ftp)://[^\s<>"']+)"#.to_string()
+    }
+
+    fn canonicalize(&self, token: &str) -> Option<String> {
+        // Sentence punctuation right after a URL is not part of it
+        let url = token.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}']);
+        let trailing = &token[url.len()..];
+
+        let (scheme, rest) = url.split_once("://")?;
+        let scheme = scheme.to_lowercase();
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (rest, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(pos) => rest.split_at(pos),
+            None => (rest, "/"),
+        };
+        let (userinfo, host) = match authority.rsplit_once('@') {
+            Some((userinfo, host)) => (Some(userinfo), host),
+            None => (None, authority),
+        };
+
+        let mut host = host.to_lowercase();
+        let default_port = match scheme.as_str() {
+            "http" => ":80",
+            "https" => ":443",
+            _ => ":21",
+        };
+        if let Some(stripped) = host.strip_suffix(default_port) {
+            host = stripped.to_string();
+        }
+
+        let mut canonical = format!("{}://", scheme);
+        if let Some(userinfo) = userinfo {
+            canonical.push_str(userinfo);
+            canonical.push('@');
+        }
+        canonical.push_str(&host);
+        canonical.push_str(path);
+
+        let params: Vec<&str> = query
+            .unwrap_or("")
+            .split('&')
+            .filter(|param| {
+                let key = param.split('=').next().unwrap_or("").to_lowercase();
+                !key.is_empty() && !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_str())
+            })
+            .collect();
+        if !params.is_empty() {
+            canonical.push('?');
+            canonical.push_str(&params.join("&"));
+        }
+        if let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) {
+            canonical.push('#');
+            canonical.push_str(fragment);
+        }
+        canonical.push_str(trailing);
+        Some(canonical)
+    }
+}
+
+struct EmailNormalizer;
+
+impl Normalizer for EmailNormalizer {
+    fn pattern(&self) -> String {
+        r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}\b".to_string()
+    }
+
+    fn canonicalize(&self, token: &str) -> Option<String> {
+        let (local, domain) = token.rsplit_once('@')?;
+        let mut domain = domain.to_lowercase();
+        if domain == "googlemail.com" {
+            domain = "gmail.com".to_string();
+        }
+
+        let mut local = local.split('+').next().unwrap_or(local).to_lowercase();
+        if DOTLESS_PROVIDERS.contains(&domain.as_str()) {
+            local.retain(|c| c != '.');
+        }
+        if local.is_empty() {
+            return None;
+        }
+        Some(format!("{}@{}", local, domain))
+    }
+}
+
+struct PhoneNormalizer {
+    default_country_code: Option<u16>,
+}
+
+impl Normalizer for PhoneNormalizer {
+    fn pattern(&self) -> String {
+        // International (+44 20 7946 0958, 0044 ...) or national with grouped digits and an area
+        // code in parentheses or behind a trunk 0 ((020) 7946-0958, 020 7946 0958), so that
+        // plain digit groups such as "100 200 300" are left alone
+        r"(?:\+|\b00)\d{1,3}[\s.-]?\(?\d{1,4}\)?(?:[\s.-]?\d{2,4}){1,4}\b|\(\d{2,4}\)[\s.-]?\d{3,4}[\s.-]\d{3,4}\b|\b0\d{1,3}[\s.-]\d{3,4}[\s.-]\d{3,4}\b"
+            .to_string()
+    }
+
+    fn canonicalize(&self, token: &str) -> Option<String> {
+        let digits: String = token.chars().filter(char::is_ascii_digit).collect();
+        let number = if token.starts_with('+') {
+            digits
+        } else if let Some(international) = digits.strip_prefix("00") {
+            international.to_string()
+        } else {
+            // National number: drop the trunk prefix and add the country code
+            let national = digits.strip_prefix('0').unwrap_or(&digits);
+            format!("{}{}", self.default_country_code?, national)
+        };
+        (8..=15).contains(&number.len()).then(|| format!("+{}", number))
+    }
+}
+
+struct DateNormalizer {
+    day_first: bool,
+}
+
+impl Normalizer for DateNormalizer {
+    fn pattern(&self) -> String {
+        format!(
+            r"\b\d{{4}}[-/.]\d{{1,2}}[-/.]\d{{1,2}}\b|\b\d{{1,2}}[-/.]\d{{1,2}}[-/.]\d{{4}}\b|(?i:\b\d{{1,2}}(?:st|nd|rd|th)?\s+{m}\.?,?\s+\d{{4}}\b|\b{m}\.?\s+\d{{1,2}}(?:st|nd|rd|th)?,?\s+\d{{4}}\b)",
+            m = MONTHS
+        )
+    }
+
+    fn canonicalize(&self, token: &str) -> Option<String> {
+        let numbers: Vec<&str> = token.split(|c: char| !c.is_ascii_digit()).filter(|n| !n.is_empty()).collect();
+        let month_name = token
+            .split(|c: char| !c.is_alphabetic())
+            .find_map(|word| month_number(&word.to_lowercase()));
+
+        let (year, month, day) = match (month_name, numbers.as_slice()) {
+            (Some(month), [a, b]) if a.len() == 4 => (a.parse().ok()?, month, b.parse().ok()?),
+            (Some(month), [a, b]) => (b.parse().ok()?, month, a.parse().ok()?),
+            (None, [y, m, d]) if y.len() == 4 => (y.parse().ok()?, m.parse().ok()?, d.parse().ok()?),
+            (None, [a, b, y]) => {
+                let (a, b): (u32, u32) = (a.parse().ok()?, b.parse().ok()?);
+                // Fall back to the other order when the preferred one has no such month
+                let (day, month) = if self.day_first { (a, b) } else { (b, a) };
+                if month > 12 { (y.parse().ok()?, day, month) } else { (y.parse().ok()?, month, day) }
+            }
+            _ => return None,
+        };
+        valid_date(year, month, day).then(|| format!("{:04}-{:02}-{:02}", year, month, day))
+    }
+}
+
+struct NumberNormalizer;
+
+impl Normalizer for NumberNormalizer {
+    fn pattern(&self) -> String {
+        // Dotted sequences are matched whole so versions and addresses can be skipped
+        r"\b\d{1,3}(?:,\d{3})+(?:\.\d+)*\b|\b\d+(?:\.\d+)*\b".to_string()
+    }
+
+    fn canonicalize(&self, token: &str) -> Option<String> {
+        // Versions and IP addresses (1.10.2, 192.168.001.010) are not numbers
+        if token.matches('.').count() > 1 {
+            return None;
+        }
+        let plain = token.replace(',', "");
+        let (int, frac) = plain.split_once('.').unwrap_or((&plain, ""));
+        let int = match int.trim_start_matches('0') {
+            "" => "0",
+            trimmed => trimmed,
+        };
+        let frac = frac.trim_end_matches('0');
+        Some(if frac.is_empty() { int.to_string() } else { format!("{}.{}", int, frac) })
+    }
+}
+
+fn month_number(word: &str) -> Option<u32> {
+    const NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    if word.len() < 3 {
+        return None;
+    }
+    NAMES.iter().position(|name| word.starts_with(name)).map(|idx| idx as u32 + 1)
+}
+
+fn valid_date(year: u32, month: u32, day: u32) -> bool {
+    let leap = (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pipeline(selected: &[DomainNormalizer]) -> NormalizerPipeline {
+        NormalizerPipeline::new(selected).unwrap()
+    }
+
+    #[test]
+    fn test_urls_and_emails() {
+        let p = pipeline(&[DomainNormalizer::Url, DomainNormalizer::Email]);
+        assert_eq!(
+            p.apply("See HTTPS://Example.COM:443/Path?id=7&utm_source=news&fbclid=x#top."),
+            "See https://example.com/Path?id=7#top."
+        );
+        assert_eq!(p.apply("http://example.com:8080"), "http://example.com:8080/");
+        assert_eq!(p.apply("Mail John.Doe+news@GoogleMail.com"), "Mail johndoe@gmail.com");
+        assert_eq!(p.apply("j.doe+x@Example.org"), "j.doe@example.org");
+    }
+
+    #[test]
+    fn test_phones_dates_and_numbers() {
+        let p = pipeline(&[
+            DomainNormalizer::Number,
+            DomainNormalizer::Phone { default_country_code: Some(44) },
+            DomainNormalizer::Date { day_first: true },
+        ]);
+        assert_eq!(p.apply("Call +44 20 7946 0958 or (020) 7946-0958"), "Call +442079460958 or +442079460958");
+        assert_eq!(p.apply("Due 03/02/2024, 2024-2-3 or 3rd February 2024"), "Due 2024-02-03, 2024-02-03 or 2024-02-03");
+        assert_eq!(p.apply("Feb 30, 2024"), "Feb 30, 2024");
+        assert_eq!(p.apply("Total 1,250.50 for 007 items"), "Total 1250.5 for 7 items");
+
+        assert_eq!(p.apply("Release 1.10.2 on 192.168.001.010"), "Release 1.10.2 on 192.168.001.010");
+
+        let phone = pipeline(&[DomainNormalizer::Phone { default_country_code: Some(44) }]);
+        assert_eq!(phone.apply("Scores 100 200 300, call 020 7946 0958"), "Scores 100 200 300, call +442079460958");
+
+        let us = pipeline(&[DomainNormalizer::Date { day_first: false }]);
+        assert_eq!(us.apply("02/03/2024 and 25/12/2024"), "2024-02-03 and 2024-12-25");
+        assert!(NormalizerPipeline::new(&[]).is_none());
+    }
+}
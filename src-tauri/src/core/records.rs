@@ -0,0 +1,425 @@
+//! Record deduplication: documents are maps of fields, compared field by field.
+//!
+//! Each field has its own normalization and similarity method. Field scores are
+//! combined by weighted average or a Fellegi-Sunter model, and only records that
+//! share a blocking key are compared.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::DynamicConfig;
+use crate::core::classifier::string_similarity;
+use crate::core::language::DEFAULT_LANGUAGE;
+use crate::core::normalization::NormalizationPipeline;
+use crate::core::normalizers::DomainNormalizer;
+use crate::state::{DedupStats, DedupStrategySettings, SimilarityMethod, SplitStrategy};
+
+/// A structured document: field name → value
+pub type Record = BTreeMap<String, String>;
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_agreement_threshold() -> f64 {
+    0.9
+}
+
+fn default_m_probability() -> f64 {
+    0.9
+}
+
+fn default_u_probability() -> f64 {
+    0.05
+}
+
+// ---------------------------------------------------------------------
+// Strategy
+// ---------------------------------------------------------------------
+
+/// How field scores are combined into a record score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ScoreCombination {
+    /// Weighted mean of the field similarities
+    #[default]
+    WeightedAverage,
+    /// Sum of Fellegi-Sunter agreement/disagreement log-weights, reported as a match probability
+    FellegiSunter,
+}
+
+/// Normalization applied to a field before comparison. Unset flags use field
+/// defaults: case-insensitive, whitespace collapsed, punctuation kept (emails and
+/// phone numbers depend on it), no stop words or stemming.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FieldNormalization {
+    pub case_sensitive: Option<bool>,
+    pub ignore_whitespace: Option<bool>,
+    pub ignore_punctuation: Option<bool>,
+    pub normalize_unicode: Option<bool>,
+    pub ignore_stopwords: Option<bool>,
+    pub stemming: Option<bool>,
+    pub encoding_normalization: Option<bool>,
+    pub domain_normalizers: Option<Vec<DomainNormalizer>>, // Canonical emails, phones, dates, ...
+}
+
+/// How one field is normalized, compared and weighted. Fields are short values
+/// compared with a string metric: `Exact`, `Levenshtein` or a `Fuzzy` algorithm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldRule {
+    pub field: String,
+    pub method: SimilarityMethod,
+    #[serde(default)]
+    pub normalization: FieldNormalization,
+    #[serde(default)]
+    pub ngram_size: Option<usize>,
+    #[serde(default = "default_weight")]
+    pub weight: f64, // Weighted average only
+    #[serde(default = "default_agreement_threshold")]
+    pub agreement_threshold: f64, // Fellegi-Sunter: similarity at which the field agrees
+    #[serde(default = "default_m_probability")]
+    pub m_probability: f64, // Fellegi-Sunter: P(agree | match)
+    #[serde(default = "default_u_probability")]
+    pub u_probability: f64, // Fellegi-Sunter: P(agree | non-match)
+}
+
+impl FieldRule {
+    /// Text strategy equivalent to this field's normalization and method
+    pub fn settings(&self) -> DedupStrategySettings {
+        let n = &self.normalization;
+        DedupStrategySettings {
+            case_sensitive: Some(n.case_sensitive.unwrap_or(false)),
+            ignore_whitespace: Some(n.ignore_whitespace.unwrap_or(true)),
+            ignore_punctuation: Some(n.ignore_punctuation.unwrap_or(false)),
+            normalize_unicode: Some(n.normalize_unicode.unwrap_or(true)),
+            ignore_stopwords: Some(n.ignore_stopwords.unwrap_or(false)),
+            stemming: Some(n.stemming.unwrap_or(false)),
+            encoding_normalization: Some(n.encoding_normalization.unwrap_or(true)),
+            domain_normalizers: n.domain_normalizers.clone(),
+            language_detection: Some(false),
+            ngram_size: self.ngram_size,
+            split_strategy: SplitStrategy::WholeText,
+            similarity_method: self.method.clone(),
+            use_parallel: Some(false),
+            record: None,
+            ..DedupStrategySettings::default()
+        }
+    }
+}
+
+/// Records are only compared with records sharing this key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockingKey {
+    pub fields: Vec<String>,
+    pub prefix: Option<usize>, // Characters kept from each field; the whole value when unset
+}
+
+impl BlockingKey {
+    /// Key of a record: lowercase alphanumerics of each key field, truncated to the prefix
+    pub fn key(&self, record: &Record) -> String {
+        self.fields
+            .iter()
+            .map(|field| {
+                let value = record.get(field).map(String::as_str).unwrap_or("");
+                let chars = value.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase);
+                match self.prefix {
+                    Some(prefix) => chars.take(prefix).collect::<String>(),
+                    None => chars.collect(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+}
+
+/// Record mode settings, stored in `DedupStrategySettings::record`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordStrategy {
+    pub fields: Vec<FieldRule>,
+    #[serde(default)]
+    pub combination: ScoreCombination,
+    pub threshold: f64,
+    #[serde(default)]
+    pub blocking_key: Option<BlockingKey>,
+}
+
+// ---------------------------------------------------------------------
+// Results
+// ---------------------------------------------------------------------
+
+/// A record grouped with an original, with the score of every compared field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordMatch {
+    pub id: usize,
+    pub similarity: f64,
+    pub field_scores: BTreeMap<String, f64>, // Fields missing from either record are absent
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordGroup {
+    pub original_id: usize,
+    pub original: Record,
+    pub duplicates: Vec<RecordMatch>,
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordResults {
+    pub groups: Vec<RecordGroup>,
+    pub stats: DedupStats, // Pruned comparisons are pairs whose blocking keys differ
+}
+
+// ---------------------------------------------------------------------
+// Matching
+// ---------------------------------------------------------------------
+
+/// Field-wise matcher for one record strategy
+pub struct RecordMatcher<'a> {
+    strategy: &'a RecordStrategy,
+    pipelines: Vec<NormalizationPipeline>, // One per field rule
+    config: DynamicConfig,
+}
+
+impl<'a> RecordMatcher<'a> {
+    /// Matcher normalizing fields with the stop words and masking rules of `config`
+    pub fn new(strategy: &'a RecordStrategy, config: DynamicConfig) -> Self {
+        Self {
+            strategy,
+            pipelines: strategy
+                .fields
+                .iter()
+                .map(|rule| NormalizationPipeline::for_strategy(&DedupStrategySettings { config: Some(config.clone()), ..rule.settings() }))
+                .collect(),
+            config,
+        }
+    }
+
+    /// Normalized value of every rule's field (None when missing or empty)
+    fn normalize(&self, record: &Record) -> Vec<Option<String>> {
+        self.strategy
+            .fields
+            .iter()
+            .zip(&self.pipelines)
+            .map(|(rule, pipeline)| {
+                record
+                    .get(&rule.field)
+                    .map(|value| pipeline.run(value, DEFAULT_LANGUAGE, &self.config, |_, _, _| {}))
+                    .filter(|value| !value.trim().is_empty())
+            })
+            .collect()
+    }
+
+    /// Score two normalized records, returning the record score and each field's similarity
+    fn compare(&self, a: &[Option<String>], b: &[Option<String>]) -> (f64, BTreeMap<String, f64>) {
+        let mut field_scores = BTreeMap::new();
+        let (mut weighted, mut total_weight, mut log_weight) = (0.0, 0.0, 0.0);
+
+        for (idx, rule) in self.strategy.fields.iter().enumerate() {
+            let (Some(value_a), Some(value_b)) = (&a[idx], &b[idx]) else {
+                continue;
+            };
+            // Validation keeps field methods to string metrics
+            let similarity = string_similarity(&rule.method, rule.ngram_size, value_a, value_b).unwrap_or(0.0);
+            field_scores.insert(rule.field.clone(), similarity);
+
+            weighted += rule.weight * similarity;
+            total_weight += rule.weight;
+            let (m, u) = (rule.m_probability, rule.u_probability);
+            log_weight += if similarity >= rule.agreement_threshold {
+                (m / u).log2()
+            } else {
+                ((1.0 - m) / (1.0 - u)).log2()
+            };
+        }
+
+        if field_scores.is_empty() {
+            return (0.0, field_scores);
+        }
+        let score = match self.strategy.combination {
+            ScoreCombination::WeightedAverage if total_weight > 0.0 => weighted / total_weight,
+            ScoreCombination::WeightedAverage => 0.0,
+            // Posterior match probability with even prior odds
+            ScoreCombination::FellegiSunter => 1.0 / (1.0 + (-log_weight).exp2()),
+        };
+        (score, field_scores)
+    }
+
+    /// Group duplicate records, comparing only records in the same block
+    pub fn deduplicate(&self, records: &[Record]) -> RecordResults {
+        let normalized: Vec<Vec<Option<String>>> = records.iter().map(|record| self.normalize(record)).collect();
+
+        let mut blocks: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (id, record) in records.iter().enumerate() {
+            let key = self.strategy.blocking_key.as_ref().map(|key| key.key(record)).unwrap_or_default();
+            blocks.entry(key).or_default().push(id);
+        }
+
+        let total_pairs = records.len() * records.len().saturating_sub(1) / 2;
+        let candidate_pairs: usize = blocks.values().map(|ids| ids.len() * ids.len().saturating_sub(1) / 2).sum();
+
+        let mut comparisons = 0;
+        let mut processed: HashSet<usize> = HashSet::new();
+        let mut groups = Vec::new();
+        for ids in blocks.values() {
+            for (pos, &i) in ids.iter().enumerate() {
+                if processed.contains(&i) {
+                    continue;
+                }
+                processed.insert(i);
+
+                let mut duplicates = Vec::new();
+                for &j in &ids[pos + 1..] {
+                    if processed.contains(&j) {
+                        continue;
+                    }
+                    comparisons += 1;
+                    let (similarity, field_scores) = self.compare(&normalized[i], &normalized[j]);
+                    if similarity >= self.strategy.threshold {
+                        processed.insert(j);
+                        duplicates.push(RecordMatch { id: j, similarity, field_scores });
+                    }
+                }
+
+                if !duplicates.is_empty() {
+                    let similarity = duplicates.iter().map(|m| m.similarity).sum::<f64>() / duplicates.len() as f64;
+                    groups.push(RecordGroup {
+                        original_id: i,
+                        original: records[i].clone(),
+                        duplicates,
+                        similarity,
+                    });
+                }
+            }
+        }
+        groups.sort_by_key(|group| group.original_id);
+
+        let duplicate_count: usize = groups.iter().map(|group| group.duplicates.len()).sum();
+        RecordResults {
+            stats: DedupStats {
+                total_items: records.len(),
+                unique_items: records.len() - duplicate_count,
+                duplicate_groups: groups.len(),
+                comparisons,
+                pruned_comparisons: total_pairs - candidate_pairs,
+            },
+            groups,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::FuzzyAlgorithm;
+
+    fn record(name: &str, email: &str, city: &str) -> Record {
+        [("name", name), ("email", email), ("city", city)]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn rule(field: &str, method: SimilarityMethod, weight: f64) -> FieldRule {
+        serde_json::from_value(serde_json::json!({ "field": field, "method": method, "weight": weight })).unwrap()
+    }
+
+    fn people() -> Vec<Record> {
+        vec![
+            record("Jonathan Smith", "J.Smith@Example.com", "Boston"),
+            record("Jonathon Smith", "j.smith@example.com", "boston"),
+            record("Maria Garcia", "maria@example.org", "Madrid"),
+            record("Mario Garcia", "mario.g@example.net", "Barcelona"),
+        ]
+    }
+
+    #[test]
+    fn test_weighted_fields_group_typo_records() {
+        let strategy = RecordStrategy {
+            fields: vec![
+                rule("name", SimilarityMethod::Fuzzy(FuzzyAlgorithm::JaroWinkler), 1.0),
+                rule("email", SimilarityMethod::Exact, 2.0),
+            ],
+            combination: ScoreCombination::WeightedAverage,
+            threshold: 0.9,
+            blocking_key: None,
+        };
+        let results = RecordMatcher::new(&strategy, DynamicConfig::default()).deduplicate(&people());
+        assert_eq!(results.groups.len(), 1);
+        assert_eq!(results.groups[0].original_id, 0);
+        assert_eq!(results.groups[0].duplicates[0].id, 1);
+        assert_eq!(results.groups[0].duplicates[0].field_scores["email"], 1.0);
+        assert_eq!(results.stats.pruned_comparisons, 0);
+    }
+
+    #[test]
+    fn test_fellegi_sunter_with_blocking() {
+        let strategy = RecordStrategy {
+            fields: vec![
+                rule("name", SimilarityMethod::Fuzzy(FuzzyAlgorithm::JaroWinkler), 1.0),
+                rule("city", SimilarityMethod::Exact, 1.0),
+            ],
+            combination: ScoreCombination::FellegiSunter,
+            threshold: 0.9,
+            blocking_key: Some(BlockingKey { fields: vec!["city".to_string()], prefix: Some(3) }),
+        };
+        let results = RecordMatcher::new(&strategy, DynamicConfig::default()).deduplicate(&people());
+        // Blocks: "bos" {0, 1}, "mad" {2}, "bar" {3}
+        assert_eq!(results.stats.pruned_comparisons, 5);
+        assert_eq!(results.stats.comparisons, 1);
+        assert_eq!(results.groups.len(), 1);
+        assert!(results.groups[0].similarity > 0.99);
+    }
+
+    #[test]
+    fn test_fields_keep_punctuation_by_default() {
+        let mut strategy = RecordStrategy {
+            fields: vec![rule("email", SimilarityMethod::Exact, 1.0)],
+            combination: ScoreCombination::WeightedAverage,
+            threshold: 1.0,
+            blocking_key: None,
+        };
+        let records = vec![record("", "j.smith@example.com", ""), record("", "jsmith@example.com", "")];
+        assert!(RecordMatcher::new(&strategy, DynamicConfig::default()).deduplicate(&records).groups.is_empty());
+
+        strategy.fields[0].normalization.ignore_punctuation = Some(true);
+        assert_eq!(RecordMatcher::new(&strategy, DynamicConfig::default()).deduplicate(&records).groups.len(), 1);
+    }
+
+    #[test]
+    fn test_fields_use_the_given_config() {
+        let mut strategy = RecordStrategy {
+            fields: vec![rule("name", SimilarityMethod::Exact, 1.0)],
+            combination: ScoreCombination::WeightedAverage,
+            threshold: 1.0,
+            blocking_key: None,
+        };
+        strategy.fields[0].normalization.ignore_stopwords = Some(true);
+        let records = vec![record("Acme Holdings", "", ""), record("Acme", "", "")];
+        assert!(RecordMatcher::new(&strategy, DynamicConfig::default()).deduplicate(&records).groups.is_empty());
+
+        let mut config = DynamicConfig::default();
+        config.add_user_stop_words("en", &["holdings".to_string()]).unwrap();
+        assert_eq!(RecordMatcher::new(&strategy, config).deduplicate(&records).groups.len(), 1);
+    }
+
+    #[test]
+    fn test_domain_normalizers_match_formatted_emails() {
+        let mut strategy = RecordStrategy {
+            fields: vec![rule("email", SimilarityMethod::Exact, 1.0)],
+            combination: ScoreCombination::WeightedAverage,
+            threshold: 1.0,
+            blocking_key: None,
+        };
+        let records = vec![record("", "John.Doe+news@GoogleMail.com", ""), record("", "johndoe@gmail.com", "")];
+        assert!(RecordMatcher::new(&strategy, DynamicConfig::default()).deduplicate(&records).groups.is_empty());
+
+        strategy.fields[0].normalization.domain_normalizers = Some(vec![DomainNormalizer::Email]);
+        let results = RecordMatcher::new(&strategy, DynamicConfig::default()).deduplicate(&records);
+        assert_eq!(results.groups.len(), 1);
+        assert_eq!(results.groups[0].duplicates[0].field_scores["email"], 1.0);
+    }
+}
@@ -0,0 +1,415 @@
+//! Script-aware text segmentation.
+
+use std::collections::{BTreeSet, HashSet};
+use std::sync::OnceLock;
+
+use jieba_rs::Jieba;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use whatlang::{detect_script, Script};
+
+// ---------------------------------------------------------------------
+// Static Resources
+// ---------------------------------------------------------------------
+
+/// Lazily loaded dictionary for Han script segmentation.
+static JIEBA: OnceLock<Jieba> = OnceLock::new();
+
+thread_local! {
+    /// ICU dictionary segmenter for Japanese and the unspaced Southeast Asian scripts
+    /// (not `Sync`; its data is compiled in, so one per thread is cheap).
+    static ICU_WORDS: icu_segmenter::WordSegmenter = icu_segmenter::WordSegmenter::new_dictionary();
+}
+
+// ---------------------------------------------------------------------
+// Word Segmentation
+// ---------------------------------------------------------------------
+
+/// How a text is cut into words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WordSegmenter {
+    /// Split on whitespace (scripts that separate words with spaces)
+    Whitespace,
+    /// Unicode word boundaries (UAX #29)
+    UnicodeWords,
+    /// Dictionary-based segmentation for Chinese text (jieba)
+    Dictionary,
+    /// ICU dictionary segmentation for Japanese, Thai, Khmer and Myanmar text
+    IcuDictionary,
+}
+
+impl WordSegmenter {
+    /// Picks the segmenter suited to a writing system.
+    pub fn for_script(script: Option<Script>) -> Self {
+        match script {
+            Some(Script::Mandarin) => WordSegmenter::Dictionary,
+            Some(Script::Hiragana | Script::Katakana | Script::Thai | Script::Khmer | Script::Myanmar) => {
+                WordSegmenter::IcuDictionary
+            }
+            _ => WordSegmenter::Whitespace,
+        }
+    }
+
+    /// Picks the segmenter for the dominant script of a text.
+    pub fn detect(text: &str) -> Self {
+        Self::for_script(detect_script(text))
+    }
+
+    /// Cuts text into words.
+    pub fn segment<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        self.segment_ranges(text).into_iter().map(|(start, end)| &text[start..end]).collect()
+    }
+
+    /// Cuts text into words, as byte ranges.
+    pub fn segment_ranges(&self, text: &str) -> Vec<(usize, usize)> {
+        match self {
+            WordSegmenter::Whitespace => {
+                let mut ranges = Vec::new();
+                let mut start = None;
+                for (idx, c) in text.char_indices() {
+                    match (c.is_whitespace(), start) {
+                        (true, Some(begin)) => {
+                            ranges.push((begin, idx));
+                            start = None;
+                        }
+                        (false, None) => start = Some(idx),
+                        _ => {}
+                    }
+                }
+                if let Some(begin) = start {
+                    ranges.push((begin, text.len()));
+                }
+                ranges
+            }
+            WordSegmenter::UnicodeWords => text
+                .unicode_word_indices()
+                .map(|(idx, word)| (idx, idx + word.len()))
+                .collect(),
+            WordSegmenter::Dictionary => {
+                // Jieba's pieces cover the whole text in order, so offsets accumulate
+                let mut offset = 0;
+                let mut ranges = Vec::new();
+                for word in JIEBA.get_or_init(Jieba::new).cut(text, true) {
+                    if word.chars().any(char::is_alphanumeric) {
+                        ranges.push((offset, offset + word.len()));
+                    }
+                    offset += word.len();
+                }
+                ranges
+            }
+            WordSegmenter::IcuDictionary => ICU_WORDS.with(|segmenter| {
+                let breaks = segmenter.segment_str(text);
+                let mut ranges = Vec::new();
+                let mut start = 0;
+                for end in breaks {
+                    // Each break closes a segment; keep words, not spaces or punctuation (the
+                    // rule status is unreliable for a dictionary word before a space)
+                    if text[start..end].chars().any(char::is_alphanumeric) {
+                        ranges.push((start, end));
+                    }
+                    start = end;
+                }
+                ranges
+            }),
+        }
+    }
+}
+
+/// Cuts text into words using the segmenter picked by its detected script.
+pub fn segment_words(text: &str) -> Vec<&str> {
+    WordSegmenter::detect(text).segment(text)
+}
+
+/// Byte ranges of the words of a text, using the segmenter picked by its detected script.
+pub fn segment_word_ranges(text: &str) -> Vec<(usize, usize)> {
+    WordSegmenter::detect(text).segment_ranges(text)
+}
+
+/// Byte range of `text[start..end]` without surrounding whitespace, None when blank.
+fn trim_range(text: &str, start: usize, end: usize) -> Option<(usize, usize)> {
+    let segment = &text[start..end];
+    let trimmed = segment.trim_start();
+    let start = start + (segment.len() - trimmed.len());
+    let end = start + trimmed.trim_end().len();
+    (start < end).then_some((start, end))
+}
+
+// ---------------------------------------------------------------------
+// Paragraph Segmentation
+// ---------------------------------------------------------------------
+
+/// Splits text on a paragraph delimiter into trimmed, non-empty byte ranges.
+pub fn paragraph_ranges(text: &str, delimiter: &str) -> Vec<(usize, usize)> {
+    if delimiter.is_empty() {
+        return trim_range(text, 0, text.len()).into_iter().collect();
+    }
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (idx, _) in text.match_indices(delimiter) {
+        ranges.extend(trim_range(text, start, idx));
+        start = idx + delimiter.len();
+    }
+    ranges.extend(trim_range(text, start, text.len()));
+    ranges
+}
+
+// ---------------------------------------------------------------------
+// Sentence Segmentation
+// ---------------------------------------------------------------------
+
+/// Closing quotes and brackets that may follow a sentence terminator.
+const CLOSERS: &[char] = &['"', '\'', ')', ']', '}', '»', '”', '’', '」', '』', '）', '】'];
+
+/// Opening quotes and brackets that may precede a word.
+const OPENERS: &[char] = &['"', '\'', '(', '[', '{', '«', '“', '‘', '「', '『', '（', '【'];
+
+/// Abbreviations (lowercase, without the final period) that do not end a sentence.
+fn abbreviations(lang_code: &str) -> &'static [&'static str] {
+    match lang_code {
+        "fr" => &[
+            "m", "mm", "mme", "mlle", "dr", "pr", "st", "ste", "cf", "av", "apr", "env", "vol", "no",
+            "p.ex", "ex", "janv", "févr", "avr", "juil", "sept", "oct", "nov", "déc",
+        ],
+        "de" => &[
+            "dr", "prof", "hr", "hrn", "fr", "z.b", "usw", "bzw", "ca", "nr", "str", "u.a", "d.h",
+            "evtl", "ggf", "vgl", "inkl", "bspw", "s", "jan", "feb", "okt", "dez",
+        ],
+        "es" => &[
+            "sr", "sra", "srta", "dr", "dra", "ud", "uds", "pág", "p.ej", "aprox", "av", "núm", "ej",
+            "ene", "abr", "ago", "dic",
+        ],
+        "it" => &["sig", "sigg", "dott", "prof", "ing", "avv", "pag", "es", "ecc", "gen", "febb"],
+        "pt" => &["sr", "sra", "dr", "dra", "prof", "pág", "ex", "av", "fev", "abr", "mai", "set", "out", "dez"],
+        "nl" => &["dhr", "mevr", "dr", "prof", "bijv", "d.w.z", "nr", "blz", "mr", "ir", "ing"],
+        "ru" => &["г", "гг", "т.е", "т.д", "т.п", "им", "ул", "стр", "см", "др", "проф", "тыс", "млн"],
+        _ => &[
+            "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "e.g", "i.e", "a.m", "p.m", "inc",
+            "ltd", "corp", "fig", "approx", "dept", "mt", "ave", "gov", "lt", "capt", "u.s", "u.k",
+            "jan", "feb", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec",
+        ],
+    }
+}
+
+/// Abbreviations that are also ordinary words ("no", "mar"). They only hold a
+/// sentence together when the next word starts in lowercase or with a digit.
+fn ambiguous_abbreviations(lang_code: &str) -> &'static [&'static str] {
+    match lang_code {
+        "fr" | "de" | "es" | "it" | "pt" | "nl" | "ru" => &[],
+        _ => &["no", "co", "est", "gen", "rep", "sen", "col", "mar"],
+    }
+}
+
+/// Rule-based sentence splitter on top of Unicode (UAX #29) sentence boundaries.
+///
+/// A boundary is kept unless it follows a known abbreviation or an initial, or
+/// falls inside a token such as a decimal number, URL or e-mail address.
+#[derive(Debug, Clone)]
+pub struct SentenceSegmenter {
+    abbreviations: &'static [&'static str],
+    ambiguous: &'static [&'static str],
+    delimiters: HashSet<char>,
+}
+
+impl SentenceSegmenter {
+    /// Creates a segmenter for an ISO 639-1 language code and the configured delimiters.
+    pub fn new(lang_code: &str, delimiters: HashSet<char>) -> Self {
+        Self {
+            abbreviations: abbreviations(lang_code),
+            ambiguous: ambiguous_abbreviations(lang_code),
+            delimiters,
+        }
+    }
+
+    /// Splits text into trimmed, non-empty sentences.
+    pub fn segment<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        self.segment_ranges(text).into_iter().map(|(start, end)| &text[start..end]).collect()
+    }
+
+    /// Splits text into trimmed, non-empty sentences, as byte ranges.
+    pub fn segment_ranges(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut boundaries: Vec<usize> = self
+            .candidate_boundaries(text)
+            .into_iter()
+            .filter(|&boundary| !self.is_false_boundary(text, boundary))
+            .collect();
+        boundaries.push(text.len());
+
+        let mut sentences = Vec::new();
+        let mut start = 0;
+        for end in boundaries {
+            sentences.extend(trim_range(text, start, end));
+            start = end;
+        }
+        sentences
+    }
+
+    /// Byte offsets where a sentence may end.
+    fn candidate_boundaries(&self, text: &str) -> BTreeSet<usize> {
+        let mut boundaries = BTreeSet::new();
+
+        // Unicode sentence boundaries after terminal punctuation (not after bare line breaks)
+        for (idx, segment) in text.split_sentence_bound_indices() {
+            let end = idx + segment.len();
+            let content = segment.trim_end().trim_end_matches(CLOSERS);
+            let ends_with_terminal = content
+                .chars()
+                .last()
+                .is_some_and(|c| !c.is_alphanumeric() && !c.is_whitespace());
+            if end < text.len() && ends_with_terminal {
+                boundaries.insert(end);
+            }
+        }
+
+        // Configured delimiters, followed by optional closers. ASCII delimiters
+        // also need whitespace after them, so "3.50" or "a.b" never split here.
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        for (i, &(_, c)) in chars.iter().enumerate() {
+            if !self.delimiters.contains(&c) {
+                continue;
+            }
+            let mut j = i + 1;
+            while j < chars.len() && CLOSERS.contains(&chars[j].1) {
+                j += 1;
+            }
+            let end = chars.get(j).map_or(text.len(), |&(pos, _)| pos);
+            let followed_by_space = chars.get(j).is_none_or(|&(_, next)| next.is_whitespace());
+            if end < text.len() && (!c.is_ascii() || followed_by_space) {
+                boundaries.insert(end);
+            }
+        }
+
+        boundaries
+    }
+
+    /// Whether a candidate boundary sits after an abbreviation or inside a token.
+    fn is_false_boundary(&self, text: &str, boundary: usize) -> bool {
+        let before = &text[..boundary];
+        let after = &text[boundary..];
+
+        // Inside a token such as 3.50, example.com or user@mail.org
+        let glued = before.chars().last().is_some_and(|c| c.is_ascii_punctuation())
+            && after.chars().next().is_some_and(|c| c.is_alphanumeric());
+        if glued {
+            return true;
+        }
+
+        let content = before.trim_end().trim_end_matches(CLOSERS);
+        let token = content.rsplit(char::is_whitespace).next().unwrap_or("");
+        if !token.ends_with('.') {
+            return false;
+        }
+
+        let word = token.trim_start_matches(OPENERS).trim_end_matches('.').to_lowercase();
+        if word.is_empty() {
+            return false;
+        }
+
+        // Known abbreviation ("Dr.", "e.g.")
+        if self.abbreviations.contains(&word.as_str()) {
+            return true;
+        }
+
+        // Abbreviation or word ("No. 5" vs "I said no. Then")
+        if self.ambiguous.contains(&word.as_str()) {
+            return after
+                .trim_start()
+                .trim_start_matches(OPENERS)
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_lowercase() || c.is_ascii_digit());
+        }
+
+        // Initial ("J. Smith") or dotted acronym ("U.N.")
+        word.split('.').all(|part| part.chars().count() == 1 && part.chars().all(char::is_alphabetic))
+    }
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitespace_scripts_keep_split_whitespace() {
+        assert_eq!(segment_words("the quick  brown fox"), vec!["the", "quick", "brown", "fox"]);
+    }
+
+    #[test]
+    fn test_chinese_is_segmented_with_dictionary() {
+        let text = "我们中出了一个叛徒";
+        assert_eq!(WordSegmenter::detect(text), WordSegmenter::Dictionary);
+        let words = segment_words(text);
+        assert!(words.len() > 1, "Chinese text should not be a single word: {:?}", words);
+        assert_eq!(words.concat(), text);
+    }
+
+    #[test]
+    fn test_thai_is_segmented_with_dictionary() {
+        let text = "สวัสดีครับ ยินดีต้อนรับ";
+        assert_eq!(WordSegmenter::detect(text), WordSegmenter::IcuDictionary);
+        assert_eq!(segment_words(text), vec!["สวัสดี", "ครับ", "ยินดี", "ต้อนรับ"]);
+    }
+
+    #[test]
+    fn test_japanese_is_segmented_with_dictionary() {
+        let text = "私は東京に住んでいます。";
+        assert_eq!(WordSegmenter::detect(text), WordSegmenter::IcuDictionary);
+        let words = segment_words(text);
+        assert_eq!(words[..4], ["私", "は", "東京", "に"]);
+        assert!(!words.contains(&"。"));
+    }
+
+    #[test]
+    fn test_sentences_keep_abbreviations_and_decimals() {
+        let segmenter = SentenceSegmenter::new("en", ['.', '!', '?'].into_iter().collect());
+        let text = "Dr. Smith paid $3.50 at 5 p.m. on Friday. Was it worth it? Visit example.com. Yes!";
+        assert_eq!(
+            segmenter.segment(text),
+            vec![
+                "Dr. Smith paid $3.50 at 5 p.m. on Friday.",
+                "Was it worth it?",
+                "Visit example.com.",
+                "Yes!",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sentences_split_after_words_that_look_like_abbreviations() {
+        let segmenter = SentenceSegmenter::new("en", ['.', '!', '?'].into_iter().collect());
+        assert_eq!(segmenter.segment("I said no. Then we left."), vec!["I said no.", "Then we left."]);
+        assert_eq!(segmenter.segment("See item no. 5 below."), vec!["See item no. 5 below."]);
+    }
+
+    #[test]
+    fn test_sentences_handle_quotes_and_lowercase_text() {
+        let segmenter = SentenceSegmenter::new("en", ['.', '!', '?'].into_iter().collect());
+        let text = "he said \"stop.\" then he left (quietly.) the end";
+        assert_eq!(
+            segmenter.segment(text),
+            vec!["he said \"stop.\"", "then he left (quietly.)", "the end"]
+        );
+    }
+
+    #[test]
+    fn test_ranges_point_into_the_text() {
+        let text = "  one two\n\n  three. Four?  ";
+        let words = segment_word_ranges(text);
+        assert_eq!(words.iter().map(|&(s, e)| &text[s..e]).collect::<Vec<_>>(), vec!["one", "two", "three.", "Four?"]);
+        assert_eq!(words[0], (2, 5));
+        let paragraphs = paragraph_ranges(text, "\n\n");
+        assert_eq!(paragraphs.iter().map(|&(s, e)| &text[s..e]).collect::<Vec<_>>(), vec!["one two", "three. Four?"]);
+        let cjk = "你好，世界";
+        let ranges = segment_word_ranges(cjk);
+        assert_eq!(ranges.iter().map(|&(s, e)| &cjk[s..e]).collect::<String>(), "你好世界");
+    }
+
+    #[test]
+    fn test_sentences_split_cjk_without_spaces() {
+        let segmenter = SentenceSegmenter::new("zh", ['。', '！', '？'].into_iter().collect());
+        assert_eq!(segmenter.segment("你好。今天天气很好！"), vec!["你好。", "今天天气很好！"]);
+    }
+}
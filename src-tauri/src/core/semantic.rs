@@ -1,6 +1,6 @@
 //! Semantic analysis module using Burn, Tokenizers, and NLP utilities.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex, OnceLock};
 
 use tokenizers::models::wordpiece::WordPiece;
@@ -13,10 +13,11 @@ use burn::nn::transformer::{TransformerEncoder, TransformerEncoderConfig};
 use burn::tensor::Tensor;
 
 use whatlang::Lang as Language;
-use stop_words::{get, LANGUAGE as StopLanguage};
-use rust_stemmers::{Algorithm, Stemmer};
-use unicode_normalization::UnicodeNormalization;
 
+use crate::config::DynamicConfig;
+use crate::core::language::{support_for_lang, DEFAULT_LANGUAGE};
+use crate::core::normalization::NormalizationPipeline;
+use crate::error::AppError;
 use crate::state::DedupStrategySettings;
 
 // Type aliases for convenience.
@@ -35,29 +36,25 @@ const EMBEDDING_DIM: usize = 768; // BERT-base dimension
 /// Lazily initialized tokenizer with WordPiece, Bert normalizer and pre-tokenizer.
 static TOKENIZER: OnceLock<Arc<Tokenizer>> = OnceLock::new();
 
-/// Stopwords mapped by language.
-static STOPWORDS: OnceLock<HashMap<Language, HashSet<String>>> = OnceLock::new();
-
-fn init_stopwords() -> HashMap<Language, HashSet<String>> {
-    let mut map = HashMap::new();
-    
-    // Map whatlang::Language to stop_words::LANGUAGE
-    let lang_mappings = [
-        (Language::Eng, StopLanguage::English),
-        (Language::Fra, StopLanguage::French),
-        (Language::Spa, StopLanguage::Spanish),
-        (Language::Por, StopLanguage::Portuguese),
-        (Language::Ita, StopLanguage::Italian),
-        (Language::Deu, StopLanguage::German),
-        (Language::Rus, StopLanguage::Russian),
-    ];
-
-    for (lang, stop_lang) in lang_mappings {
-        let stopwords = get(stop_lang);
-        map.insert(lang, stopwords.into_iter().collect());
-    }
-
-    map
+/// Builds the WordPiece tokenizer with Bert normalizer and pre-tokenizer.
+fn build_tokenizer() -> Result<Tokenizer, AppError> {
+    let mut vocab = HashMap::new();
+    vocab.insert("[UNK]".to_string(), 0);
+
+    let wordpiece = WordPiece::builder()
+        .unk_token("[UNK]".into())
+        .max_input_chars_per_word(100)
+        .continuing_subword_prefix("##".into())
+        .vocab(vocab)
+        .build()
+        .map_err(|e| AppError::model_load_failed("wordpiece", format!("Failed to build WordPiece model: {}", e)))?;
+
+    let mut tokenizer = Tokenizer::new(wordpiece);
+    // Configure the normalizer and pre-tokenizer for BERT.
+    let normalizer = BertNormalizer::new(true, true, Some(true), true);
+    tokenizer.with_normalizer(Some(normalizer));
+    tokenizer.with_pre_tokenizer(Some(BertPreTokenizer));
+    Ok(tokenizer)
 }
 
 // ---------------------------------------------------------------------
@@ -73,8 +70,9 @@ pub struct TextEncoder {
 }
 
 impl TextEncoder {
-    /// Creates a new `TextEncoder` with a default transformer encoder and tokenizer.
-    pub fn new() -> Self {
+    /// Creates a new `TextEncoder` with a default transformer encoder and tokenizer,
+    /// reporting model loading failures.
+    pub fn new() -> Result<Self, AppError> {
         let device = DefaultDevice::default();
 
         // Configure and initialize transformer encoder.
@@ -86,31 +84,19 @@ impl TextEncoder {
         );
         let encoder = Arc::new(Mutex::new(config.init(&device)));
 
-        let tokenizer = TOKENIZER.get_or_init(|| {
-            let mut vocab = HashMap::new();
-            vocab.insert("[UNK]".to_string(), 0);
-            
-            let wordpiece = WordPiece::builder()
-                .unk_token("[UNK]".into())
-                .max_input_chars_per_word(100)
-                .continuing_subword_prefix("##".into())
-                .vocab(vocab)
-                .build()
-                .expect("Failed to build WordPiece model");
-
-            let mut tokenizer = Tokenizer::new(wordpiece);
-            // Configure the normalizer and pre-tokenizer for BERT.
-            let normalizer = BertNormalizer::new(true, true, Some(true), true);
-            tokenizer.with_normalizer(Some(normalizer));
-            tokenizer.with_pre_tokenizer(Some(BertPreTokenizer));
-            Arc::new(tokenizer)
-        });
-
-        Self {
+        let tokenizer = match TOKENIZER.get() {
+            Some(tokenizer) => tokenizer,
+            None => {
+                let tokenizer = build_tokenizer()?;
+                TOKENIZER.get_or_init(|| Arc::new(tokenizer))
+            }
+        };
+
+        Ok(Self {
             encoder,
             tokenizer: Arc::clone(tokenizer),
             device,
-        }
+        })
     }
 
     /// Encodes text into a tensor.
@@ -182,15 +168,29 @@ pub struct DocumentVector {
 pub struct SemanticAnalyzer {
     encoder: TextEncoder,
     language_cache: HashMap<String, Language>,
+    settings: DedupStrategySettings,
+    config: DynamicConfig,
+    normalization: NormalizationPipeline, // Built from `settings` whenever they change
 }
 
 impl SemanticAnalyzer {
-    /// Creates a new `SemanticAnalyzer`.
-    pub fn new() -> Self {
-        Self {
-            encoder: TextEncoder::new(),
+    /// Creates a new `SemanticAnalyzer` with default settings, loading its encoder.
+    pub fn new() -> Result<Self, AppError> {
+        let settings = DedupStrategySettings::default();
+        Ok(Self {
+            encoder: TextEncoder::new()?,
             language_cache: HashMap::new(),
-        }
+            config: settings.config.clone().unwrap_or_default(),
+            normalization: NormalizationPipeline::for_strategy(&settings),
+            settings,
+        })
+    }
+
+    /// Replaces the settings used to preprocess and compare texts.
+    pub fn update_settings(&mut self, settings: DedupStrategySettings) {
+        self.config = settings.config.clone().unwrap_or_default();
+        self.normalization = NormalizationPipeline::for_strategy(&settings);
+        self.settings = settings;
     }
 
     /// Detects the language of the given text, using cache if available.
@@ -212,70 +212,15 @@ impl SemanticAnalyzer {
         detected_lang
     }
 
-    /// Preprocesses text based on the provided settings.
-    pub fn preprocess_text(
-        &self,
-        text: &str,
-        lang: Option<Language>,
-        settings: &DedupStrategySettings,
-    ) -> String {
-        let mut processed = if settings.case_sensitive.unwrap_or(false) {
-            text.to_string()
-        } else {
-            text.to_lowercase()
-        };
-
-        // Normalize whitespace if required.
-        if !settings.ignore_whitespace.unwrap_or(false) {
-            processed = processed.split_whitespace().collect::<Vec<_>>().join(" ");
-        }
-
-        // Remove punctuation if enabled.
-        if settings.ignore_punctuation.unwrap_or(false) {
-            processed = processed.chars().filter(|c| !c.is_ascii_punctuation()).collect();
-        }
-
-        // Normalize Unicode if requested.
-        if settings.normalize_unicode.unwrap_or(false) {
-            processed = processed.nfc().collect();
-        }
-
-        // Remove stopwords if enabled.
-        if settings.ignore_stopwords.unwrap_or(false) {
-            if let Some(lang) = lang {
-                if let Some(stopwords) = STOPWORDS.get() {
-                    if let Some(lang_stopwords) = stopwords.get(&lang) {
-                        processed = processed
-                            .split_whitespace()
-                            .filter(|word| !lang_stopwords.contains(*word))
-                            .collect::<Vec<_>>()
-                            .join(" ");
-                    }
-                }
-            }
-        }
-
-        // Apply stemming if enabled.
-        if settings.stemming.unwrap_or(false) {
-            if let Some(lang) = lang {
-                let stemmer = match lang {
-                    Language::Eng => Stemmer::create(Algorithm::English),
-                    Language::Fra => Stemmer::create(Algorithm::French),
-                    Language::Spa => Stemmer::create(Algorithm::Spanish),
-                    Language::Por => Stemmer::create(Algorithm::Portuguese),
-                    Language::Ita => Stemmer::create(Algorithm::Italian),
-                    Language::Deu => Stemmer::create(Algorithm::German),
-                    Language::Rus => Stemmer::create(Algorithm::Russian),
-                    _ => return processed, // Unsupported language; return unstemmed.
-                };
-
-                processed = processed
-                    .split_whitespace()
-                    .map(|word| stemmer.stem(word).to_string())
-                    .collect::<Vec<_>>()
-                    .join(" ");
-            }
-        }
+    /// Preprocesses text based on the analyzer's settings.
+    pub fn preprocess_text(&self, text: &str, lang: Option<Language>) -> String {
+        let settings = &self.settings;
+        // Same steps, in the same order, as the classifier
+        let lang_code = lang
+            .and_then(support_for_lang)
+            .map(|support| support.code)
+            .unwrap_or(DEFAULT_LANGUAGE);
+        let mut processed = self.normalization.run(text, lang_code, &self.config, |_, _, _| {});
 
         // Create n-grams if requested.
         if let Some(n) = settings.ngram_size {
@@ -302,14 +247,14 @@ impl SemanticAnalyzer {
     }
 
     /// Encodes text into a document vector.
-    pub fn encode(&mut self, text: &str, settings: &DedupStrategySettings) -> DocumentVector {
-        let lang = if settings.language_detection.unwrap_or(false) {
+    pub fn encode(&mut self, text: &str) -> DocumentVector {
+        let lang = if self.settings.language_detection.unwrap_or(false) {
             self.detect_language(text)
         } else {
             None
         };
 
-        let processed_text = self.preprocess_text(text, lang, settings);
+        let processed_text = self.preprocess_text(text, lang);
         let vector = self.encoder.encode_text(&processed_text);
         let token_count = processed_text.split_whitespace().count();
 
@@ -321,16 +266,11 @@ impl SemanticAnalyzer {
     }
 
     /// Calculates the semantic similarity between two texts.
-    pub fn calculate_semantic_similarity(
-        &mut self,
-        text1: &str,
-        text2: &str,
-        settings: &DedupStrategySettings,
-    ) -> f64 {
-        let doc1 = self.encode(text1, settings);
-        let doc2 = self.encode(text2, settings);
+    pub fn calculate_semantic_similarity(&mut self, text1: &str, text2: &str) -> f64 {
+        let doc1 = self.encode(text1);
+        let doc2 = self.encode(text2);
 
-        let language_penalty = if settings.language_detection.unwrap_or(false) {
+        let language_penalty = if self.settings.language_detection.unwrap_or(false) {
             match (doc1.language, doc2.language) {
                 (Some(l1), Some(l2)) if l1 != l2 => 0.8, // 20% penalty for different languages.
                 _ => 1.0,
@@ -344,7 +284,7 @@ impl SemanticAnalyzer {
             .calculate_similarity(&doc1.vector, &doc2.vector);
 
         // Apply threshold if specified
-         let threshold = settings.similarity_threshold;
+         let threshold = self.settings.similarity_threshold;
         if similarity < threshold {
             return 0.0;
         }
@@ -353,15 +293,6 @@ impl SemanticAnalyzer {
     }
 }
 
-impl Default for SemanticAnalyzer {
-    fn default() -> Self {
-        Self {
-            encoder: TextEncoder::new(),
-            language_cache: HashMap::new(),
-        }
-    }
-}
-
 // ---------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------
@@ -372,20 +303,18 @@ mod tests {
 
     #[test]
     fn test_semantic_similarity() {
-        let mut analyzer = SemanticAnalyzer::new();
+        let mut analyzer = SemanticAnalyzer::new().unwrap();
 
         let text1 = "The quick brown fox jumps over the lazy dog";
         let text2 = "A fast brown fox leaps above a sleepy canine";
-        let default_settings = DedupStrategySettings::default();
-
-        let similarity = analyzer.calculate_semantic_similarity(text1, text2, &default_settings);
+        let similarity = analyzer.calculate_semantic_similarity(text1, text2);
         assert!(
             similarity > 0.7,
             "Similar sentences should have a high similarity score"
         );
 
         let text3 = "Completely unrelated text about programming computers";
-        let similarity = analyzer.calculate_semantic_similarity(text1, text3, &default_settings);
+        let similarity = analyzer.calculate_semantic_similarity(text1, text3);
         assert!(
             similarity < 0.5,
             "Different sentences should have a low similarity score"
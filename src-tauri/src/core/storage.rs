@@ -1,2 +1,62 @@
 // Storage layer components
-pub struct StorageLayer;
+//! JSON documents persisted under a root directory (the app config directory).
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use crate::error::AppError;
+
+pub struct StorageLayer {
+    root: PathBuf,
+}
+
+impl StorageLayer {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the JSON document stored under `name`
+    pub fn path_for(&self, name: &str) -> PathBuf {
+        self.root.join(format!("{}.json", name))
+    }
+
+    /// Load a document, returning `None` if it has never been saved
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, AppError> {
+        let path = self.path_for(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| AppError::io(Some(path.display().to_string()), "read", e))?;
+        let value = serde_json::from_str(&content).map_err(|e| {
+            AppError::storage(
+                Some(path.display().to_string()),
+                "parse",
+                format!("Corrupt document at line {}: {}", e.line(), e),
+            )
+        })?;
+        Ok(Some(value))
+    }
+
+    /// Save a document, replacing any previous version atomically
+    pub fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<(), AppError> {
+        let path = self.path_for(name);
+        let path_str = Some(path.display().to_string());
+
+        fs::create_dir_all(&self.root)
+            .map_err(|e| AppError::storage(path_str.clone(), "create directory", e.to_string()))?;
+
+        let content = serde_json::to_string_pretty(value)?;
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content)
+            .map_err(|e| AppError::storage(path_str.clone(), "write", e.to_string()))?;
+        fs::rename(&tmp_path, &path)
+            .map_err(|e| AppError::storage(path_str, "rename", e.to_string()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+//! Corpus-level term weighting (TF-IDF and BM25) for weighted cosine similarity.
+
+use std::collections::HashMap;
+
+use tfidf::tf::LogNormalizationTf;
+use tfidf::Tf;
+
+use crate::core::classifier::Features;
+
+/// BM25 term frequency saturation
+const BM25_K1: f64 = 1.2;
+/// BM25 document length normalization
+const BM25_B: f64 = 0.75;
+
+/// How term weights are computed from the corpus statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermWeighting {
+    /// Log-normalized term frequency times smoothed inverse document frequency
+    TfIdf,
+    /// Okapi BM25 term weights
+    Bm25,
+}
+
+/// Document frequencies and lengths over every indexed text.
+#[derive(Debug, Clone, Default)]
+pub struct CorpusStats {
+    doc_freq: HashMap<String, usize>,
+    num_docs: usize,
+    avg_len: f64,
+}
+
+impl CorpusStats {
+    /// Collects document frequencies from the features of every text.
+    pub fn new(features: &[Features]) -> Self {
+        let mut doc_freq: HashMap<String, usize> = HashMap::new();
+        let mut total_len = 0;
+        for doc in features {
+            total_len += doc_len(doc);
+            for term in doc.keys() {
+                *doc_freq.entry(term.clone()).or_insert(0) += 1;
+            }
+        }
+
+        let num_docs = features.len();
+        Self {
+            doc_freq,
+            num_docs,
+            avg_len: if num_docs == 0 { 0.0 } else { total_len as f64 / num_docs as f64 },
+        }
+    }
+
+    /// Number of texts containing `term`.
+    pub fn doc_freq(&self, term: &str) -> usize {
+        self.doc_freq.get(term).copied().unwrap_or(0)
+    }
+
+    /// Smoothed inverse document frequency, `ln((1 + N) / (1 + df)) + 1`.
+    pub fn idf(&self, term: &str) -> f64 {
+        ((1 + self.num_docs) as f64 / (1 + self.doc_freq(term)) as f64).ln() + 1.0
+    }
+
+    /// BM25 inverse document frequency, kept positive for terms found in most texts.
+    pub fn bm25_idf(&self, term: &str) -> f64 {
+        let df = self.doc_freq(term) as f64;
+        ((self.num_docs as f64 - df + 0.5) / (df + 0.5) + 1.0).ln()
+    }
+
+    /// Weight of a term in a document.
+    pub fn weight(&self, term: &String, doc: &Features, weighting: TermWeighting) -> f64 {
+        match weighting {
+            TermWeighting::TfIdf => LogNormalizationTf::tf(term, doc) * self.idf(term),
+            TermWeighting::Bm25 => {
+                let tf = doc.get(term).copied().unwrap_or(0) as f64;
+                let len_ratio = if self.avg_len > 0.0 { doc_len(doc) as f64 / self.avg_len } else { 1.0 };
+                let saturated = tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * len_ratio));
+                saturated * self.bm25_idf(term)
+            }
+        }
+    }
+
+    /// Cosine similarity of two documents' weighted term vectors.
+    pub fn cosine(&self, doc1: &Features, doc2: &Features, weighting: TermWeighting) -> f64 {
+        let weights1: HashMap<&String, f64> = doc1.keys().map(|t| (t, self.weight(t, doc1, weighting))).collect();
+        let weights2: HashMap<&String, f64> = doc2.keys().map(|t| (t, self.weight(t, doc2, weighting))).collect();
+
+        let dot: f64 = weights1
+            .iter()
+            .filter_map(|(term, w1)| weights2.get(term).map(|w2| w1 * w2))
+            .sum();
+        let norm1 = weights1.values().map(|w| w * w).sum::<f64>().sqrt();
+        let norm2 = weights2.values().map(|w| w * w).sum::<f64>().sqrt();
+
+        if norm1 == 0.0 || norm2 == 0.0 {
+            0.0
+        } else {
+            (dot / (norm1 * norm2)).clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Total number of term occurrences in a document.
+fn doc_len(doc: &Features) -> usize {
+    doc.values().sum()
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(text: &str) -> Features {
+        let mut counts = Features::new();
+        for word in text.split_whitespace() {
+            *counts.entry(word.to_string()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_shared_rare_terms_outweigh_shared_boilerplate() {
+        let corpus = vec![
+            doc("the cat"),
+            doc("the dog"),
+            doc("cat food"),
+            doc("the weather"),
+            doc("the news"),
+        ];
+        let stats = CorpusStats::new(&corpus);
+        assert!(stats.idf("cat") > stats.idf("the"));
+
+        // Jaccard scores both pairs 1/3; weighting separates them
+        for weighting in [TermWeighting::TfIdf, TermWeighting::Bm25] {
+            let shared_rare = stats.cosine(&corpus[0], &corpus[2], weighting);
+            let shared_common = stats.cosine(&corpus[0], &corpus[1], weighting);
+            assert!(shared_rare > shared_common, "{:?}: {} <= {}", weighting, shared_rare, shared_common);
+            assert!((stats.cosine(&corpus[0], &corpus[0], weighting) - 1.0).abs() < 1e-9);
+            assert_eq!(stats.cosine(&corpus[0], &Features::new(), weighting), 0.0);
+        }
+    }
+}
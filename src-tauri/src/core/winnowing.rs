@@ -0,0 +1,196 @@
+//! Winnowing document fingerprints (Schleimer et al.) for partial-overlap detection.
+//!
+//! Texts are reduced to lowercase alphanumerics, cut into character (or word)
+//! k-grams and hashed with BLAKE3; winnowing keeps the minimum hash of every
+//! window. Each fingerprint remembers where its k-gram sits in the original text.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::segmentation::segment_word_ranges;
+
+/// Default k-gram size, in characters
+pub const DEFAULT_KGRAM_SIZE: usize = 5;
+/// Default winnowing window, in k-grams
+pub const DEFAULT_WINDOW: usize = 4;
+
+/// A selected k-gram hash and its char offsets in the original text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub hash: String, // Hex of the first 8 bytes of the BLAKE3 digest
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Fingerprints shared by two documents and how much of each one they cover.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FingerprintMatch {
+    /// Share of A's fingerprints found in B, |A ∩ B| / |A|
+    pub containment_a: f64,
+    /// Share of B's fingerprints found in A, |A ∩ B| / |B|
+    pub containment_b: f64,
+    /// Fingerprints of A whose hash also appears in B
+    pub matched_a: Vec<Fingerprint>,
+    /// Fingerprints of B whose hash also appears in A
+    pub matched_b: Vec<Fingerprint>,
+}
+
+/// Winnows a text into fingerprints, using k-grams of `k` chars and windows of `window` k-grams.
+/// Texts shorter than k yield a single fingerprint.
+pub fn fingerprints(text: &str, k: usize, window: usize) -> Vec<Fingerprint> {
+    let (k, window) = (k.max(1), window.max(1));
+
+    // Lowercase alphanumerics with their char offset in the original text
+    let chars: Vec<(usize, char)> = text
+        .chars()
+        .enumerate()
+        .filter(|(_, c)| c.is_alphanumeric())
+        .flat_map(|(pos, c)| c.to_lowercase().map(move |lower| (pos, lower)))
+        .collect();
+    if chars.is_empty() {
+        return Vec::new();
+    }
+
+    let kgrams: Vec<Fingerprint> = chars
+        .windows(k.min(chars.len()))
+        .map(|gram| Fingerprint {
+            hash: hash_kgram(gram.iter().map(|&(_, c)| c).collect::<String>().as_bytes()),
+            start: gram[0].0,
+            end: gram[gram.len() - 1].0 + 1,
+        })
+        .collect();
+    winnow(&kgrams, window)
+}
+
+/// Winnows a text into fingerprints, using k-grams of `k` words and windows of `window` k-grams.
+/// Words are lowercased and stripped of punctuation. Texts shorter than k yield a single fingerprint.
+pub fn word_fingerprints(text: &str, k: usize, window: usize) -> Vec<Fingerprint> {
+    let (k, window) = (k.max(1), window.max(1));
+
+    // Lowercase words with their char range in the original text
+    let mut words: Vec<(usize, usize, String)> = Vec::new();
+    let (mut last_byte, mut last_char) = (0, 0);
+    for (start, end) in segment_word_ranges(text) {
+        let char_start = last_char + text[last_byte..start].chars().count();
+        let char_end = char_start + text[start..end].chars().count();
+        (last_byte, last_char) = (end, char_end);
+        let word: String = text[start..end]
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if !word.is_empty() {
+            words.push((char_start, char_end, word));
+        }
+    }
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let kgrams: Vec<Fingerprint> = words
+        .windows(k.min(words.len()))
+        .map(|gram| Fingerprint {
+            // Unit separator between words, so "a b" and "ab" differ
+            hash: hash_kgram(gram.iter().map(|(_, _, word)| word.as_str()).collect::<Vec<_>>().join("\u{1f}").as_bytes()),
+            start: gram[0].0,
+            end: gram[gram.len() - 1].1,
+        })
+        .collect();
+    winnow(&kgrams, window)
+}
+
+/// Keeps the rightmost minimum hash of every window of `window` k-grams, once per position
+pub fn winnow(kgrams: &[Fingerprint], window: usize) -> Vec<Fingerprint> {
+    if kgrams.is_empty() {
+        return Vec::new();
+    }
+    let mut selected: Vec<Fingerprint> = Vec::new();
+    let mut last_pick = None;
+    for (offset, candidates) in kgrams.windows(window.clamp(1, kgrams.len())).enumerate() {
+        let (idx, min) = candidates
+            .iter()
+            .enumerate()
+            .rev()
+            .min_by(|(_, a), (_, b)| a.hash.cmp(&b.hash))
+            .expect("windows are never empty");
+        if last_pick != Some(offset + idx) {
+            last_pick = Some(offset + idx);
+            selected.push(min.clone());
+        }
+    }
+    selected
+}
+
+/// Share of `a`'s distinct fingerprints that also appear in `b`.
+pub fn containment<'a>(a: impl IntoIterator<Item = &'a str>, b: &HashSet<&str>) -> f64 {
+    let hashes: HashSet<&str> = a.into_iter().collect();
+    if hashes.is_empty() {
+        return 0.0;
+    }
+    hashes.iter().filter(|hash| b.contains(*hash)).count() as f64 / hashes.len() as f64
+}
+
+/// Compares the fingerprints of two documents.
+pub fn compare(a: &[Fingerprint], b: &[Fingerprint]) -> FingerprintMatch {
+    let hashes_a: HashSet<&str> = a.iter().map(|fp| fp.hash.as_str()).collect();
+    let hashes_b: HashSet<&str> = b.iter().map(|fp| fp.hash.as_str()).collect();
+
+    FingerprintMatch {
+        containment_a: containment(hashes_a.iter().copied(), &hashes_b),
+        containment_b: containment(hashes_b.iter().copied(), &hashes_a),
+        matched_a: a.iter().filter(|fp| hashes_b.contains(fp.hash.as_str())).cloned().collect(),
+        matched_b: b.iter().filter(|fp| hashes_a.contains(fp.hash.as_str())).cloned().collect(),
+    }
+}
+
+pub(crate) fn hash_kgram(bytes: &[u8]) -> String {
+    hex::encode(&blake3::hash(bytes).as_bytes()[..8])
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copied_passage_is_contained() {
+        let passage = "It was the best of times, it was the worst of times";
+        let source = format!("Chapter one. {} and the rest is different.", passage);
+        let short = fingerprints(passage, DEFAULT_KGRAM_SIZE, DEFAULT_WINDOW);
+        let long = fingerprints(&source, DEFAULT_KGRAM_SIZE, DEFAULT_WINDOW);
+
+        let result = compare(&short, &long);
+        assert_eq!(result.containment_a, 1.0, "the whole passage was copied");
+        assert!(result.containment_b < 1.0, "the source has more than the passage");
+
+        // Matched positions fall inside the copied passage of the source
+        let offset = source.find(passage).unwrap();
+        for fp in &result.matched_b {
+            assert!(fp.start >= offset && fp.end <= offset + passage.chars().count());
+        }
+    }
+
+    #[test]
+    fn test_fingerprints_ignore_case_and_punctuation() {
+        let a = fingerprints("Hello, World!", 4, 2);
+        let b = fingerprints("hello world", 4, 2);
+        let hashes = |fps: &[Fingerprint]| fps.iter().map(|fp| fp.hash.clone()).collect::<Vec<_>>();
+        assert_eq!(hashes(&a), hashes(&b));
+        assert_eq!(fingerprints("abc", 5, 4).len(), 1);
+        assert!(fingerprints("  ...  ", 5, 4).is_empty());
+    }
+
+    #[test]
+    fn test_word_fingerprints_follow_word_order() {
+        let hashes = |fps: Vec<Fingerprint>| fps.into_iter().map(|fp| fp.hash).collect::<Vec<_>>();
+        let a = word_fingerprints("The quick, brown fox!", 2, 1);
+        assert_eq!(hashes(a.clone()), hashes(word_fingerprints("the quick brown fox", 2, 1)));
+        assert_ne!(hashes(a.clone()), hashes(word_fingerprints("fox brown quick the", 2, 1)));
+        assert_eq!((a[0].start, a[0].end), (0, 10), "positions cover \"The quick,\"");
+        assert_eq!(word_fingerprints("short", 3, 4).len(), 1);
+    }
+}
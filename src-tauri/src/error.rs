@@ -5,6 +5,8 @@ use std::fmt;
 pub struct AppError {
     pub code: ErrorCode,
     pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<ErrorDetails>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,6 +16,40 @@ pub enum ErrorCode {
     StrategyUpdateError,
     InvalidInput,
     InternalError,
+    NotFound,
+    InvalidStrategy,
+    ImportFailed,
+    ModelLoadFailed,
+    StorageError,
+    Cancelled,
+}
+
+/// Structured context attached to an error so the UI can react precisely
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum ErrorDetails {
+    NotFound {
+        resource: String,
+        id: String,
+    },
+    InvalidStrategy {
+        field: String,
+        suggestion: Option<String>,
+    },
+    ImportFailed {
+        file: String,
+        line: Option<usize>,
+    },
+    ModelLoadFailed {
+        model: String,
+    },
+    StorageError {
+        path: Option<String>,
+        operation: String,
+    },
+    Cancelled {
+        operation: String,
+    },
 }
 
 impl fmt::Display for ErrorCode {
@@ -24,25 +60,123 @@ impl fmt::Display for ErrorCode {
             ErrorCode::StrategyUpdateError => write!(f, "E003"),
             ErrorCode::InvalidInput => write!(f, "E004"),
             ErrorCode::InternalError => write!(f, "E005"),
+            ErrorCode::NotFound => write!(f, "E006"),
+            ErrorCode::InvalidStrategy => write!(f, "E007"),
+            ErrorCode::ImportFailed => write!(f, "E008"),
+            ErrorCode::ModelLoadFailed => write!(f, "E009"),
+            ErrorCode::StorageError => write!(f, "E010"),
+            ErrorCode::Cancelled => write!(f, "E011"),
         }
     }
 }
 
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
 impl AppError {
     pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
         Self {
             code,
             message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: ErrorDetails) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn not_found(resource: impl Into<String>, id: impl ToString) -> Self {
+        let resource = resource.into();
+        let id = id.to_string();
+        Self::new(ErrorCode::NotFound, format!("{} '{}' not found", resource, id))
+            .with_details(ErrorDetails::NotFound { resource, id })
+    }
+
+    pub fn invalid_strategy(field: impl Into<String>, message: impl Into<String>, suggestion: Option<String>) -> Self {
+        Self::new(ErrorCode::InvalidStrategy, message)
+            .with_details(ErrorDetails::InvalidStrategy { field: field.into(), suggestion })
+    }
+
+    pub fn import_failed(file: impl Into<String>, line: Option<usize>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::ImportFailed, message)
+            .with_details(ErrorDetails::ImportFailed { file: file.into(), line })
+    }
+
+    pub fn model_load_failed(model: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::ModelLoadFailed, message)
+            .with_details(ErrorDetails::ModelLoadFailed { model: model.into() })
+    }
+
+    pub fn storage(path: Option<String>, operation: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::StorageError, message)
+            .with_details(ErrorDetails::StorageError { path, operation: operation.into() })
+    }
+
+    /// I/O failure on a path: a missing file is `NotFound` with its path, anything else a storage error
+    pub fn io(path: Option<String>, operation: impl Into<String>, err: std::io::Error) -> Self {
+        match err.kind() {
+            std::io::ErrorKind::NotFound => {
+                let id = path.unwrap_or_default();
+                Self::new(ErrorCode::NotFound, format!("File '{}' not found: {}", id, err))
+                    .with_details(ErrorDetails::NotFound { resource: "File".to_string(), id })
+            }
+            _ => Self::storage(path, operation, format!("I/O error: {}", err)),
         }
     }
+
+    pub fn cancelled(operation: impl Into<String>) -> Self {
+        let operation = operation.into();
+        Self::new(ErrorCode::Cancelled, format!("{} was cancelled", operation))
+            .with_details(ErrorDetails::Cancelled { operation })
+    }
 }
 
 impl From<serde_json::Error> for AppError {
     fn from(err: serde_json::Error) -> Self {
-        Self::new(
-            ErrorCode::SerializationError,
-            format!("JSON serialization error: {}", err)
-        )
+        use serde_json::error::Category;
+
+        match err.classify() {
+            Category::Io => Self::new(
+                ErrorCode::SerializationError,
+                format!("JSON serialization error: {}", err)
+            ),
+            Category::Syntax | Category::Data | Category::Eof => Self::new(
+                ErrorCode::DeserializationError,
+                format!("JSON deserialization error: {}", err)
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(err: std::io::Error) -> Self {
+        // Without the call site there is no path or operation; use `AppError::io` where they are known
+        Self::io(None, "I/O", err)
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(err: anyhow::Error) -> Self {
+        // Preserve the precise error when the chain carries a known type
+        let err = match err.downcast::<AppError>() {
+            Ok(app_err) => return app_err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<serde_json::Error>() {
+            Ok(json_err) => return json_err.into(),
+            Err(err) => err,
+        };
+        match err.downcast::<std::io::Error>() {
+            Ok(io_err) => io_err.into(),
+            Err(err) => Self::new(ErrorCode::InternalError, format!("{:#}", err)),
+        }
     }
 }
 
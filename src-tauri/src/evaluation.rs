@@ -0,0 +1,482 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::core::classifier::Features;
+use crate::core::engine::DeduplicationEngine;
+use crate::core::weighting::CorpusStats;
+use crate::error::{AppError, ErrorCode};
+use crate::state::{DedupResults, DedupStrategySettings, FeatureExtraction, FuzzyAlgorithm, SimilarityMethod};
+
+// ---------------------------------------------------------------------
+// Gold Standard
+// ---------------------------------------------------------------------
+
+/// Labeled dataset: texts plus the known duplicates among them, as pairs
+/// and/or clusters of text indices. Texts not labeled are unique.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GoldStandard {
+    pub texts: Vec<String>,
+    #[serde(default)]
+    pub pairs: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub clusters: Vec<Vec<usize>>,
+}
+
+impl GoldStandard {
+    /// Load a gold file (JSON), checking every label refers to a text
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let file = path.display().to_string();
+        let content = fs::read_to_string(path)
+            .map_err(|e| AppError::import_failed(file.clone(), None, e.to_string()))?;
+        let gold: GoldStandard = serde_json::from_str(&content)
+            .map_err(|e| AppError::import_failed(file.clone(), Some(e.line()), e.to_string()))?;
+
+        let mut labeled = gold.pairs.iter().flat_map(|&(a, b)| [a, b]).chain(gold.clusters.iter().flatten().copied());
+        if let Some(id) = labeled.find(|&id| id >= gold.texts.len()) {
+            return Err(AppError::import_failed(
+                file,
+                None,
+                format!("Label refers to text {} but the file has {} texts", id, gold.texts.len()),
+            ));
+        }
+        Ok(gold)
+    }
+
+    /// Cluster label of every text, merging overlapping pairs and clusters
+    pub fn labels(&self) -> Vec<usize> {
+        let groups = self
+            .pairs
+            .iter()
+            .map(|&(a, b)| vec![a, b])
+            .chain(self.clusters.iter().cloned());
+        labels_from_groups(self.texts.len(), groups)
+    }
+}
+
+// ---------------------------------------------------------------------
+// Metrics
+// ---------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Scores {
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+impl Scores {
+    fn new(precision: f64, recall: f64) -> Self {
+        let f1 = if precision + recall == 0.0 { 0.0 } else { 2.0 * precision * recall / (precision + recall) };
+        Self { precision, recall, f1 }
+    }
+}
+
+/// How predicted duplicate groups compare with the gold standard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationReport {
+    pub pairwise: Scores,
+    pub b_cubed: Scores,
+    pub adjusted_rand_index: f64,
+    pub true_positive_pairs: usize,
+    pub false_positive_pairs: usize,
+    pub false_negative_pairs: usize,
+    pub predicted_groups: Vec<Vec<usize>>,
+}
+
+/// Run a strategy over the gold texts in a fresh engine and score its groups
+pub fn evaluate(strategy: &DedupStrategySettings, gold: &GoldStandard) -> Result<EvaluationReport, AppError> {
+    Ok(score(predicted_groups(&gold_engine(strategy, gold)?.run()?), gold))
+}
+
+/// Engine holding the gold texts, ingested in order so ids are text indices
+fn gold_engine(strategy: &DedupStrategySettings, gold: &GoldStandard) -> Result<DeduplicationEngine, AppError> {
+    strategy.ensure_valid()?;
+    let mut engine = DeduplicationEngine::new(strategy.clone());
+    for text in &gold.texts {
+        engine.ingest(text.clone());
+    }
+    Ok(engine)
+}
+
+/// Reported duplicate groups as text ids, original first
+fn predicted_groups(results: &DedupResults) -> Vec<Vec<usize>> {
+    results
+        .duplicate_groups
+        .iter()
+        .map(|group| std::iter::once(group.original_id).chain(group.members.iter().map(|member| member.id)).collect())
+        .collect()
+}
+
+/// Score predicted groups of text indices against the gold standard
+pub fn score(predicted_groups: Vec<Vec<usize>>, gold: &GoldStandard) -> EvaluationReport {
+    let n = gold.texts.len();
+    let predicted = labels_from_groups(n, predicted_groups.iter().cloned());
+    let expected = gold.labels();
+
+    // Pairwise: every pair of texts placed in the same group
+    let (mut tp, mut fp, mut fn_) = (0, 0, 0);
+    for i in 0..n {
+        for j in i + 1..n {
+            match (predicted[i] == predicted[j], expected[i] == expected[j]) {
+                (true, true) => tp += 1,
+                (true, false) => fp += 1,
+                (false, true) => fn_ += 1,
+                (false, false) => {}
+            }
+        }
+    }
+    let pairwise = Scores::new(ratio(tp, tp + fp), ratio(tp, tp + fn_));
+
+    // B-cubed: per-text overlap of its predicted and gold clusters
+    let mut contingency: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut predicted_sizes: HashMap<usize, usize> = HashMap::new();
+    let mut expected_sizes: HashMap<usize, usize> = HashMap::new();
+    for i in 0..n {
+        *contingency.entry((predicted[i], expected[i])).or_insert(0) += 1;
+        *predicted_sizes.entry(predicted[i]).or_insert(0) += 1;
+        *expected_sizes.entry(expected[i]).or_insert(0) += 1;
+    }
+    let (mut b_precision, mut b_recall) = (0.0, 0.0);
+    for i in 0..n {
+        let overlap = contingency[&(predicted[i], expected[i])] as f64;
+        b_precision += overlap / predicted_sizes[&predicted[i]] as f64;
+        b_recall += overlap / expected_sizes[&expected[i]] as f64;
+    }
+    let b_cubed = if n == 0 {
+        Scores::new(1.0, 1.0)
+    } else {
+        Scores::new(b_precision / n as f64, b_recall / n as f64)
+    };
+
+    EvaluationReport {
+        pairwise,
+        b_cubed,
+        adjusted_rand_index: adjusted_rand_index(n, &contingency, &predicted_sizes, &expected_sizes),
+        true_positive_pairs: tp,
+        false_positive_pairs: fp,
+        false_negative_pairs: fn_,
+        predicted_groups,
+    }
+}
+
+/// Adjusted Rand index from the contingency table of the two partitions
+fn adjusted_rand_index(
+    n: usize,
+    contingency: &HashMap<(usize, usize), usize>,
+    predicted_sizes: &HashMap<usize, usize>,
+    expected_sizes: &HashMap<usize, usize>,
+) -> f64 {
+    let pairs = |k: usize| (k * k.saturating_sub(1) / 2) as f64;
+    let index: f64 = contingency.values().map(|&k| pairs(k)).sum();
+    let sum_predicted: f64 = predicted_sizes.values().map(|&k| pairs(k)).sum();
+    let sum_expected: f64 = expected_sizes.values().map(|&k| pairs(k)).sum();
+    let total = pairs(n);
+    if total == 0.0 {
+        return 1.0;
+    }
+
+    let expected_index = sum_predicted * sum_expected / total;
+    let max_index = (sum_predicted + sum_expected) / 2.0;
+    if max_index == expected_index {
+        // Both partitions are all singletons or a single cluster
+        return if index == expected_index { 1.0 } else { 0.0 };
+    }
+    (index - expected_index) / (max_index - expected_index)
+}
+
+/// `num / den`, taking an empty denominator as a perfect score
+fn ratio(num: usize, den: usize) -> f64 {
+    if den == 0 { 1.0 } else { num as f64 / den as f64 }
+}
+
+/// Cluster label per text; overlapping groups are merged and ungrouped texts are singletons
+fn labels_from_groups(n: usize, groups: impl Iterator<Item = Vec<usize>>) -> Vec<usize> {
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+
+    for group in groups {
+        let members: Vec<usize> = group.into_iter().filter(|&id| id < n).collect();
+        for pair in members.windows(2) {
+            let (a, b) = (find(&mut parent, pair[0]), find(&mut parent, pair[1]));
+            parent[a] = b;
+        }
+    }
+    (0..n).map(|i| find(&mut parent, i)).collect()
+}
+
+// ---------------------------------------------------------------------
+// Tuning
+// ---------------------------------------------------------------------
+
+/// Candidate values swept by `tune`; missing lists use the defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TuningOptions {
+    pub thresholds: Option<Vec<f64>>,
+    pub ngram_sizes: Option<Vec<usize>>, // Only swept for Fuzzy(NGram), and for Winnowing and CodeClone without shingles
+    pub methods: Option<Vec<SimilarityMethod>>,
+    pub feature_extractions: Option<Vec<FeatureExtraction>>,
+    pub save_as: Option<String>, // Save the best strategy as a user preset with this name
+}
+
+impl TuningOptions {
+    fn thresholds(&self) -> Vec<f64> {
+        self.thresholds.clone().unwrap_or_else(|| (1..=20).map(|step| step as f64 * 0.05).collect())
+    }
+
+    fn ngram_sizes(&self) -> Vec<usize> {
+        self.ngram_sizes.clone().unwrap_or_else(|| vec![2, 3, 4, 5])
+    }
+
+    fn methods(&self) -> Vec<SimilarityMethod> {
+        self.methods.clone().unwrap_or_else(|| {
+            vec![
+                SimilarityMethod::Exact,
+                SimilarityMethod::Levenshtein,
+                SimilarityMethod::Fuzzy(FuzzyAlgorithm::DamerauLevenshtein),
+                SimilarityMethod::Fuzzy(FuzzyAlgorithm::JaroWinkler),
+                SimilarityMethod::Fuzzy(FuzzyAlgorithm::Soundex),
+                SimilarityMethod::Fuzzy(FuzzyAlgorithm::NGram),
+                SimilarityMethod::TfIdfCosine,
+                SimilarityMethod::Bm25,
+                SimilarityMethod::Winnowing,
+                SimilarityMethod::CodeClone,
+            ]
+        })
+    }
+
+    fn feature_extractions(&self) -> Vec<FeatureExtraction> {
+        self.feature_extractions.clone().unwrap_or_else(|| {
+            vec![
+                FeatureExtraction::BagOfUnits,
+                FeatureExtraction::WordShingles(2),
+                FeatureExtraction::WordShingles(3),
+                FeatureExtraction::CharShingles(3),
+                FeatureExtraction::CharShingles(5),
+            ]
+        })
+    }
+
+    fn check(&self) -> Result<(), AppError> {
+        if let Some(threshold) = self.thresholds().into_iter().find(|t| !(0.0..=1.0).contains(t)) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Thresholds must be between 0.0 and 1.0, got {}", threshold),
+            ));
+        }
+        if self.ngram_sizes().contains(&0) {
+            return Err(AppError::new(ErrorCode::InvalidInput, "N-gram sizes must be at least 1"));
+        }
+        let empty_shingles = |extraction: &FeatureExtraction| {
+            matches!(extraction, FeatureExtraction::WordShingles(0) | FeatureExtraction::CharShingles(0))
+        };
+        if self.feature_extractions().iter().any(empty_shingles) {
+            return Err(AppError::new(ErrorCode::InvalidInput, "Shingle sizes must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// One point of a precision/recall curve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub threshold: f64,
+    pub scores: Scores,
+}
+
+/// Precision/recall over the swept thresholds for one method configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TuningCurve {
+    pub method: SimilarityMethod,
+    pub ngram_size: Option<usize>,
+    pub feature_extraction: FeatureExtraction,
+    pub points: Vec<CurvePoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TuningReport {
+    pub curves: Vec<TuningCurve>,
+    /// Strategy with the best pairwise F1
+    pub best: DedupStrategySettings,
+    pub best_report: EvaluationReport,
+    pub saved_preset: Option<String>,
+}
+
+/// Index cache key: the feature extraction, plus (code fingerprints, k-gram size) for fingerprint
+/// methods. It covers only the swept settings; normalization and masks come from `base` and are
+/// the same for every entry, so the cache must not outlive one `tune` call.
+type IndexKey = (FeatureExtraction, Option<(bool, Option<usize>)>);
+
+/// Sweep methods, feature extractions, n-gram sizes and thresholds over the gold texts, starting
+/// from `base`. Features are indexed once per feature-affecting setting and reused across the sweep.
+pub fn tune(base: &DedupStrategySettings, gold: &GoldStandard, options: &TuningOptions) -> Result<TuningReport, AppError> {
+    options.check()?;
+    let thresholds = options.thresholds();
+
+    let mut engine = gold_engine(base, gold)?;
+
+    // Fingerprint features depend on the method and k-gram size; every other method shares one index per extraction
+    let mut cache: HashMap<IndexKey, (Vec<Features>, CorpusStats)> = HashMap::new();
+    let mut curves = Vec::new();
+    let mut best: Option<(DedupStrategySettings, EvaluationReport)> = None;
+
+    for method in options.methods() {
+        for feature_extraction in options.feature_extractions() {
+            let shingles = !matches!(feature_extraction, FeatureExtraction::BagOfUnits);
+            let ngram_sizes: Vec<Option<usize>> = match method {
+                SimilarityMethod::Fuzzy(FuzzyAlgorithm::NGram) => options.ngram_sizes().into_iter().map(Some).collect(),
+                // Shingles set the fingerprint k-gram size themselves
+                SimilarityMethod::Winnowing | SimilarityMethod::CodeClone if !shingles => {
+                    options.ngram_sizes().into_iter().map(Some).collect()
+                }
+                _ => vec![base.ngram_size],
+            };
+
+            for ngram_size in ngram_sizes {
+                let mut strategy = DedupStrategySettings {
+                    similarity_method: method.clone(),
+                    ngram_size,
+                    feature_extraction: Some(feature_extraction),
+                    ..base.clone()
+                };
+                engine.update_strategy(strategy.clone())?;
+
+                let fingerprints = match method {
+                    SimilarityMethod::Winnowing => Some((false, ngram_size)),
+                    SimilarityMethod::CodeClone => Some((true, ngram_size)),
+                    _ => None,
+                };
+                let (features, corpus) = cache.entry((feature_extraction, fingerprints)).or_insert_with(|| {
+                    let features = engine.classifier().index();
+                    let corpus = CorpusStats::new(&features);
+                    (features, corpus)
+                });
+
+                let mut points = Vec::with_capacity(thresholds.len());
+                for &threshold in &thresholds {
+                    strategy.similarity_threshold = threshold;
+                    engine.update_strategy(strategy.clone())?;
+                    let report = score(predicted_groups(&engine.run_indexed(features, corpus)?), gold);
+                    points.push(CurvePoint { threshold, scores: report.pairwise });
+
+                    let improves = best.as_ref().is_none_or(|(_, current)| report.pairwise.f1 > current.pairwise.f1);
+                    if improves {
+                        best = Some((strategy.clone(), report));
+                    }
+                }
+                curves.push(TuningCurve { method: method.clone(), ngram_size, feature_extraction, points });
+            }
+        }
+    }
+
+    let (best, best_report) = best.ok_or_else(|| {
+        AppError::new(ErrorCode::InvalidInput, "Tuning needs at least one method and one threshold")
+    })?;
+    Ok(TuningReport {
+        curves,
+        best,
+        best_report,
+        saved_preset: None,
+    })
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gold(n: usize, clusters: Vec<Vec<usize>>) -> GoldStandard {
+        GoldStandard {
+            texts: (0..n).map(|i| format!("text {}", i)).collect(),
+            pairs: Vec::new(),
+            clusters,
+        }
+    }
+
+    #[test]
+    fn test_perfect_prediction_scores_one() {
+        let gold = gold(5, vec![vec![0, 1, 2], vec![3, 4]]);
+        let report = score(vec![vec![2, 1, 0], vec![4, 3]], &gold);
+        assert_eq!(report.pairwise, Scores::new(1.0, 1.0));
+        assert_eq!(report.b_cubed, Scores::new(1.0, 1.0));
+        assert!((report.adjusted_rand_index - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_partial_prediction() {
+        // Gold {0,1,2}; predicted {0,1} and a wrong pair {2,3}
+        let gold = gold(4, vec![vec![0, 1, 2]]);
+        let report = score(vec![vec![0, 1], vec![2, 3]], &gold);
+        assert_eq!((report.true_positive_pairs, report.false_positive_pairs, report.false_negative_pairs), (1, 1, 2));
+        assert_eq!(report.pairwise.precision, 0.5);
+        assert!((report.pairwise.recall - 1.0 / 3.0).abs() < 1e-9);
+        // B-cubed precision: texts 0,1 → 1; texts 2,3 → 1/2 (each shares a group with one wrong text)
+        assert!((report.b_cubed.precision - 0.75).abs() < 1e-9);
+        assert!(report.adjusted_rand_index < 1.0);
+    }
+
+    #[test]
+    fn test_pairs_are_merged_transitively() {
+        let gold = GoldStandard { pairs: vec![(0, 1), (1, 2)], ..gold(4, Vec::new()) };
+        let labels = gold.labels();
+        assert!(labels[0] == labels[1] && labels[1] == labels[2] && labels[2] != labels[3]);
+    }
+
+    #[test]
+    fn test_evaluation_applies_the_duplicate_limit() {
+        let gold = GoldStandard {
+            texts: ["apple pie", "apple pie", "banana bread", "banana bread"].iter().map(|text| text.to_string()).collect(),
+            pairs: vec![(0, 1), (2, 3)],
+            clusters: Vec::new(),
+        };
+        let strategy = DedupStrategySettings { max_duplicate_count: Some(1), ..DedupStrategySettings::default() };
+        let report = evaluate(&strategy, &gold).unwrap();
+        assert_eq!(report.predicted_groups, vec![vec![0, 1]]);
+        assert_eq!(report.false_negative_pairs, 1);
+    }
+
+    #[test]
+    fn test_tuning_finds_a_separating_threshold() {
+        let gold = GoldStandard {
+            texts: vec![
+                "the quick brown fox jumps over the lazy dog".to_string(),
+                "the quick brown fox jumped over the lazy dog".to_string(),
+                "an entirely different sentence about cooking pasta".to_string(),
+                "another unrelated line on the weather today".to_string(),
+            ],
+            pairs: vec![(0, 1)],
+            clusters: Vec::new(),
+        };
+        let options = TuningOptions {
+            methods: Some(vec![SimilarityMethod::Exact, SimilarityMethod::TfIdfCosine]),
+            feature_extractions: Some(vec![FeatureExtraction::BagOfUnits]),
+            ..TuningOptions::default()
+        };
+        let report = tune(&DedupStrategySettings::default(), &gold, &options).unwrap();
+        assert_eq!(report.curves.len(), 2);
+        assert_eq!(report.curves[0].points.len(), 20);
+        assert_eq!(report.best_report.pairwise.f1, 1.0);
+        assert_eq!(report.best_report.predicted_groups, vec![vec![0, 1]]);
+
+        let invalid = TuningOptions { thresholds: Some(vec![1.5]), ..TuningOptions::default() };
+        assert!(tune(&DedupStrategySettings::default(), &gold, &invalid).is_err());
+
+        // The default sweep covers every method, fuzzy algorithm and feature extraction
+        let sweep = TuningOptions { thresholds: Some(vec![0.8]), ..TuningOptions::default() };
+        let report = tune(&DedupStrategySettings::default(), &gold, &sweep).unwrap();
+        let swept = |method: &SimilarityMethod| report.curves.iter().filter(|curve| format!("{:?}", curve.method) == format!("{:?}", method)).count();
+        assert_eq!(swept(&SimilarityMethod::Fuzzy(FuzzyAlgorithm::JaroWinkler)), 5);
+        assert_eq!(swept(&SimilarityMethod::Fuzzy(FuzzyAlgorithm::NGram)), 5 * 4);
+        assert_eq!(swept(&SimilarityMethod::Winnowing), 4 + 4);
+        assert_eq!(swept(&SimilarityMethod::CodeClone), 4 + 4);
+    }
+}
@@ -5,9 +5,15 @@ pub mod core;
 pub mod state;
 pub mod config;
 pub mod error;
+pub mod evaluation;
+pub mod presets;
+pub mod validation;
 pub use state::*;
 
+use tauri::Manager;
 use tokio::sync::Mutex;
+use crate::config::{DynamicConfig, UserConfig, USER_CONFIG_DOCUMENT};
+use crate::core::storage::StorageLayer;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -17,13 +23,53 @@ pub fn run() {
             SimilarityMethod::default(),
         )))
         .plugin(tauri_plugin_shell::init())
+        .setup(|app| {
+            // Restore the user's stop words and delimiters from the last session
+            let storage = StorageLayer::new(app.path().app_config_dir()?);
+            if let Some(user_config) = storage.load::<UserConfig>(USER_CONFIG_DOCUMENT)? {
+                let state = app.state::<Mutex<DedupManager>>();
+                let mut manager = state.blocking_lock();
+                manager.engine_mut().update_config(DynamicConfig::from_user_config(user_config));
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::add_text,
             commands::get_text,
+            commands::get_document_metadata,
+            commands::compare_fingerprints,
+            commands::find_code_clone,
+            commands::explain_pair,
+            commands::diff_texts,
+            commands::render_diff_html,
+            commands::evaluate_strategy,
+            commands::tune_strategy,
+            commands::add_record,
+            commands::get_record,
+            commands::deduplicate_records,
+            commands::mine_log_templates,
+            commands::find_identical_files,
+            commands::find_similar_files,
+            commands::apply_file_action,
             commands::clear,
             commands::update_strategy,
+            commands::validate_strategy,
             commands::get_strategy,
             commands::deduplicate_texts,
+            commands::list_presets,
+            commands::apply_preset,
+            commands::save_preset,
+            commands::update_preset,
+            commands::delete_preset,
+            commands::import_presets,
+            commands::export_presets,
+            commands::get_config,
+            commands::add_stop_words,
+            commands::remove_stop_words,
+            commands::set_sentence_delimiters,
+            commands::set_paragraph_delimiter,
+            commands::set_masking_rules,
+            commands::get_builtin_masks,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,312 @@
+use std::fs;
+use std::path::Path;
+use crate::core::storage::StorageLayer;
+use crate::error::{AppError, ErrorCode};
+use crate::state::{
+    ComparisonScope, DedupStrategyPreset, DedupStrategySettings, FuzzyAlgorithm, SimilarityMethod,
+    SplitStrategy,
+};
+
+/// Storage document holding the user-defined presets
+const PRESETS_DOCUMENT: &str = "presets";
+
+// ---------------------------------------------------------------------
+// Built-in Presets
+// ---------------------------------------------------------------------
+
+fn builtin(name: &str, description: &str, settings: DedupStrategySettings) -> DedupStrategyPreset {
+    DedupStrategyPreset {
+        name: name.to_string(),
+        description: description.to_string(),
+        settings,
+        builtin: true,
+    }
+}
+
+/// **Read-only presets shipped with the application.**
+pub fn builtin_presets() -> Vec<DedupStrategyPreset> {
+    vec![
+        builtin(
+            "exact lines",
+            "Identical lines, ignoring only surrounding whitespace",
+            DedupStrategySettings {
+                case_sensitive: Some(true),
+                ignore_whitespace: Some(true),
+                ignore_punctuation: Some(false),
+                normalize_unicode: Some(false),
+                ignore_stopwords: Some(false),
+                stemming: Some(false),
+                language_detection: Some(false),
+                similarity_threshold: 1.0,
+                split_strategy: SplitStrategy::WholeText,
+                similarity_method: SimilarityMethod::Exact,
+                encoding_normalization: Some(false),
+                config: None,
+                ..DedupStrategySettings::default()
+            },
+        ),
+        builtin(
+            "near-duplicate paragraphs",
+            "Paragraphs that share most of their wording after stemming and stopword removal",
+            DedupStrategySettings {
+                ngram_size: Some(3),
+                similarity_threshold: 0.7,
+                split_strategy: SplitStrategy::Paragraphs,
+                similarity_method: SimilarityMethod::Fuzzy(FuzzyAlgorithm::NGram),
+                config: None,
+                ..DedupStrategySettings::default()
+            },
+        ),
+        builtin(
+            "product titles",
+            "Short titles with small spelling, casing or punctuation differences",
+            DedupStrategySettings {
+                ignore_stopwords: Some(false),
+                stemming: Some(false),
+                similarity_threshold: 0.85,
+                split_strategy: SplitStrategy::Words,
+                similarity_method: SimilarityMethod::Fuzzy(FuzzyAlgorithm::JaroWinkler),
+                config: None,
+                ..DedupStrategySettings::default()
+            },
+        ),
+        builtin(
+            "person names",
+            "Names with typos, accents or swapped letters",
+            DedupStrategySettings {
+                ignore_stopwords: Some(false),
+                stemming: Some(false),
+                language_detection: Some(false),
+                min_length: Some(2),
+                similarity_threshold: 0.85,
+                split_strategy: SplitStrategy::Characters,
+                similarity_method: SimilarityMethod::Fuzzy(FuzzyAlgorithm::JaroWinkler),
+                config: None,
+                ..DedupStrategySettings::default()
+            },
+        ),
+        builtin(
+            "code snippets",
+            "Copy-pasted code that differs only in layout",
+            DedupStrategySettings {
+                case_sensitive: Some(true),
+                ignore_punctuation: Some(false),
+                normalize_unicode: Some(false),
+                ignore_stopwords: Some(false),
+                stemming: Some(false),
+                language_detection: Some(false),
+                ngram_size: Some(5),
+                similarity_threshold: 0.8,
+                split_strategy: SplitStrategy::Words,
+                comparison_scope: ComparisonScope::Global,
+                similarity_method: SimilarityMethod::Fuzzy(FuzzyAlgorithm::NGram),
+                encoding_normalization: Some(false),
+                config: None,
+                ..DedupStrategySettings::default()
+            },
+        ),
+        builtin(
+            "log lines",
+            "Log lines that differ only in a few tokens such as ids or timestamps",
+            DedupStrategySettings {
+                ignore_stopwords: Some(false),
+                stemming: Some(false),
+                language_detection: Some(false),
+                similarity_threshold: 0.85,
+                split_strategy: SplitStrategy::Words,
+                similarity_method: SimilarityMethod::Fuzzy(FuzzyAlgorithm::DamerauLevenshtein),
+                config: None,
+                ..DedupStrategySettings::default()
+            },
+        ),
+    ]
+}
+
+// ---------------------------------------------------------------------
+// Preset Library
+// ---------------------------------------------------------------------
+
+/// Built-in presets plus the user presets persisted in storage
+pub struct PresetLibrary {
+    user: Vec<DedupStrategyPreset>,
+}
+
+impl PresetLibrary {
+    /// Load the user presets from storage
+    pub fn load(storage: &StorageLayer) -> Result<Self, AppError> {
+        let user = storage.load(PRESETS_DOCUMENT)?.unwrap_or_default();
+        Ok(Self { user })
+    }
+
+    /// Persist the user presets
+    pub fn save(&self, storage: &StorageLayer) -> Result<(), AppError> {
+        storage.save(PRESETS_DOCUMENT, &self.user)
+    }
+
+    /// All presets, built-in first
+    pub fn list(&self) -> Vec<DedupStrategyPreset> {
+        builtin_presets().into_iter().chain(self.user.iter().cloned()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<DedupStrategyPreset> {
+        self.list().into_iter().find(|preset| preset.name == name)
+    }
+
+    /// Add a new user preset
+    pub fn add(&mut self, preset: DedupStrategyPreset) -> Result<(), AppError> {
+        Self::check(&preset)?;
+        if self.get(&preset.name).is_some() {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("A preset named '{}' already exists", preset.name),
+            ));
+        }
+        self.user.push(DedupStrategyPreset { builtin: false, ..preset });
+        Ok(())
+    }
+
+    /// Replace the user preset called `name` (the replacement may be renamed)
+    pub fn update(&mut self, name: &str, preset: DedupStrategyPreset) -> Result<(), AppError> {
+        Self::check(&preset)?;
+        self.ensure_mutable(name)?;
+        if preset.name != name && self.get(&preset.name).is_some() {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("A preset named '{}' already exists", preset.name),
+            ));
+        }
+        let slot = self
+            .user
+            .iter_mut()
+            .find(|existing| existing.name == name)
+            .ok_or_else(|| AppError::not_found("Preset", name))?;
+        *slot = DedupStrategyPreset { builtin: false, ..preset };
+        Ok(())
+    }
+
+    /// Remove the user preset called `name`
+    pub fn remove(&mut self, name: &str) -> Result<(), AppError> {
+        self.ensure_mutable(name)?;
+        let before = self.user.len();
+        self.user.retain(|preset| preset.name != name);
+        if self.user.len() == before {
+            return Err(AppError::not_found("Preset", name));
+        }
+        Ok(())
+    }
+
+    /// Import presets from a JSON file holding one preset or a list of them.
+    /// User presets with the same name are overwritten.
+    pub fn import(&mut self, path: &Path) -> Result<Vec<String>, AppError> {
+        let file = path.display().to_string();
+        let content = fs::read_to_string(path)
+            .map_err(|e| AppError::import_failed(file.clone(), None, e.to_string()))?;
+
+        let value: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| AppError::import_failed(file.clone(), Some(e.line()), e.to_string()))?;
+        let presets: Vec<DedupStrategyPreset> = if value.is_array() {
+            serde_json::from_value(value)
+        } else {
+            serde_json::from_value(value).map(|preset| vec![preset])
+        }
+        .map_err(|e| AppError::import_failed(file.clone(), None, e.to_string()))?;
+
+        let mut imported = Vec::with_capacity(presets.len());
+        for preset in presets {
+            if builtin_presets().iter().any(|builtin| builtin.name == preset.name) {
+                return Err(AppError::import_failed(
+                    file,
+                    None,
+                    format!("'{}' is the name of a built-in preset", preset.name),
+                ));
+            }
+            if let Err(e) = Self::check(&preset) {
+                return Err(AppError::import_failed(file, None, format!("'{}': {}", preset.name, e.message)));
+            }
+            self.user.retain(|existing| existing.name != preset.name);
+            imported.push(preset.name.clone());
+            self.user.push(DedupStrategyPreset { builtin: false, ..preset });
+        }
+        Ok(imported)
+    }
+
+    /// Export presets to a JSON file: the named ones, or every user preset
+    pub fn export(&self, path: &Path, names: Option<&[String]>) -> Result<usize, AppError> {
+        let presets: Vec<DedupStrategyPreset> = match names {
+            Some(names) => names
+                .iter()
+                .map(|name| self.get(name).ok_or_else(|| AppError::not_found("Preset", name)))
+                .collect::<Result<_, _>>()?,
+            None => self.user.clone(),
+        };
+
+        let content = serde_json::to_string_pretty(&presets)?;
+        fs::write(path, content).map_err(|e| {
+            AppError::storage(Some(path.display().to_string()), "export", e.to_string())
+        })?;
+        Ok(presets.len())
+    }
+
+    fn ensure_mutable(&self, name: &str) -> Result<(), AppError> {
+        if builtin_presets().iter().any(|preset| preset.name == name) {
+            return Err(AppError::new(
+                ErrorCode::InvalidInput,
+                format!("Built-in preset '{}' cannot be modified", name),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check(preset: &DedupStrategyPreset) -> Result<(), AppError> {
+        if preset.name.trim().is_empty() {
+            return Err(AppError::new(ErrorCode::InvalidInput, "Preset name must not be empty"));
+        }
+        preset.settings.ensure_valid()
+    }
+}
+
+// ---------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_presets_are_valid() {
+        for preset in builtin_presets() {
+            assert!(preset.settings.validate().is_empty(), "{} should be valid", preset.name);
+        }
+        let paragraphs = builtin_presets().into_iter().find(|preset| preset.name == "near-duplicate paragraphs").unwrap();
+        assert!(matches!(paragraphs.settings.split_strategy, SplitStrategy::Paragraphs));
+    }
+
+    #[test]
+    fn test_user_preset_lifecycle() {
+        let dir = std::env::temp_dir().join(format!("jdeduplix-presets-{}", uuid::Uuid::new_v4()));
+        let storage = StorageLayer::new(&dir);
+
+        let mut library = PresetLibrary::load(&storage).unwrap();
+        let preset = DedupStrategyPreset {
+            name: "mine".to_string(),
+            description: "custom".to_string(),
+            settings: DedupStrategySettings::default(),
+            builtin: false,
+        };
+        library.add(preset.clone()).unwrap();
+        assert!(library.add(preset.clone()).is_err(), "Duplicate names are rejected");
+        assert!(library.remove("exact lines").is_err(), "Built-in presets are read-only");
+        library.save(&storage).unwrap();
+
+        // Round-trip through storage and an export file
+        let mut library = PresetLibrary::load(&storage).unwrap();
+        assert!(library.get("mine").is_some());
+        let export_path = dir.join("export.json");
+        assert_eq!(library.export(&export_path, None).unwrap(), 1);
+        library.remove("mine").unwrap();
+        assert_eq!(library.import(&export_path).unwrap(), vec!["mine".to_string()]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
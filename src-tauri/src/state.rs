@@ -7,7 +7,15 @@ use std::str::FromStr;
 use strsim;
 use jaro_winkler::jaro_winkler;
 use rphonetic::{Encoder, Soundex};
-use crate::core::classifier::TextClassifier;
+use crate::core::blocking::BlockingRule;
+use crate::core::code::CodeStrategy;
+use crate::core::engine::DeduplicationEngine;
+use crate::core::language::DetectedLanguage;
+use crate::core::logs::LogStrategy;
+use crate::core::normalization::PipelineStep;
+use crate::core::normalizers::DomainNormalizer;
+use crate::core::records::RecordStrategy;
+use crate::core::winnowing::DEFAULT_WINDOW;
 
 // ---------------------------------------------------------------------
 // Core Types
@@ -38,6 +46,10 @@ pub enum SimilarityMethod {
     Semantic,        // Semantic similarity using embeddings
     Levenshtein,    // Basic edit distance
     Fuzzy(FuzzyAlgorithm),  // Various fuzzy matching algorithms
+    TfIdfCosine,    // Cosine over TF-IDF weighted features
+    Bm25,           // Cosine over BM25 weighted features
+    Winnowing,      // Containment of winnowed k-gram fingerprints (k = ngram_size)
+    CodeClone,      // Winnowing over lexed source tokens, see `code` (k = ngram_size tokens)
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -83,6 +95,20 @@ pub enum SplitStrategy {
     WholeText,
 }
 
+/// How comparison features are extracted from a normalized text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FeatureExtraction {
+    BagOfUnits,          // Unordered set of split units
+    WordShingles(usize), // Contiguous k-grams of words
+    CharShingles(usize), // Contiguous k-grams of characters
+}
+
+impl Default for FeatureExtraction {
+    fn default() -> Self {
+        FeatureExtraction::BagOfUnits
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ComparisonScope {
     Local,
@@ -135,16 +161,24 @@ pub struct DedupStrategySettings {
     pub stemming: Option<bool>,
     pub language_detection: Option<bool>,
     pub ngram_size: Option<usize>,
+    pub winnowing_window: Option<usize>, // Window, in k-grams, for the Winnowing method
     pub min_length: Option<usize>,
     pub similarity_threshold: f64,
     pub max_duplicate_count: Option<usize>, // Maximum number of duplicates to process
     pub split_strategy: SplitStrategy,
+    pub feature_extraction: Option<FeatureExtraction>,
     pub comparison_scope: ComparisonScope,
     pub similarity_method: SimilarityMethod,
     pub use_parallel: Option<bool>,
     pub encoding_normalization: Option<bool>,
     pub adaptive_thresholding: Option<bool>,//
     pub config: Option<DynamicConfig>,
+    pub record: Option<RecordStrategy>, // Field-wise matching for records
+    pub blocking: Option<Vec<BlockingRule>>, // Candidate pairs must pass every rule
+    pub domain_normalizers: Option<Vec<DomainNormalizer>>, // Applied before the other normalization steps
+    pub normalization: Option<Vec<PipelineStep>>, // Ordered steps; replaces the flags above when set
+    pub log: Option<LogStrategy>, // Template mining over the lines of the texts
+    pub code: Option<CodeStrategy>, // Lexer settings for the CodeClone method
 }
 
 impl Default for DedupStrategySettings {
@@ -158,16 +192,24 @@ impl Default for DedupStrategySettings {
             stemming: Some(true),
             language_detection: Some(true),
             ngram_size: None,
+            winnowing_window: Some(DEFAULT_WINDOW),
             min_length: None,
             similarity_threshold: 0.5,
             max_duplicate_count: Some(1000), // Default to 1000 duplicates
             split_strategy: SplitStrategy::Words,
+            feature_extraction: Some(FeatureExtraction::BagOfUnits),
             comparison_scope: ComparisonScope::Global,
             similarity_method: SimilarityMethod::Exact,
             use_parallel: Some(true),
             encoding_normalization: Some(true),
             adaptive_thresholding: Some(false),
             config: Some(DynamicConfig::default()),
+            record: None,
+            blocking: None,
+            domain_normalizers: None,
+            normalization: None,
+            log: None,
+            code: None,
         }
     }
 }
@@ -177,6 +219,8 @@ pub struct DedupStrategyPreset {
     pub name: String,
     pub description: String,
     pub settings: DedupStrategySettings,
+    #[serde(default)]
+    pub builtin: bool, // Shipped with the app and read-only
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -184,6 +228,63 @@ pub struct DuplicateGroup {
     pub original: String,
     pub duplicates: Vec<String>,
     pub similarity: f64,
+    pub original_id: usize,
+    pub members: Vec<DuplicateMember>, // One per duplicate, in the same order
+}
+
+/// Where a duplicate sits in its document and which parts match the original
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateMember {
+    pub id: usize,         // Parent document id
+    pub similarity: f64,   // Similarity to the original
+    pub span: Span,        // Covers the matching units, or the whole text when none align
+    pub matches: Vec<SpanMatch>,
+}
+
+/// A range of a text, as byte offsets (for Rust) and char offsets (for the frontend)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+}
+
+impl Span {
+    /// Span of the bytes `start..end` of `text`
+    pub fn from_bytes(text: &str, start: usize, end: usize) -> Self {
+        let char_start = text[..start].chars().count();
+        Self {
+            start,
+            end,
+            char_start,
+            char_end: char_start + text[start..end].chars().count(),
+        }
+    }
+
+    /// Smallest span covering both
+    pub fn cover(&self, other: &Span) -> Self {
+        Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+            char_start: self.char_start.min(other.char_start),
+            char_end: self.char_end.max(other.char_end),
+        }
+    }
+}
+
+/// Aligned units of the original and a duplicate with the same normalized content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpanMatch {
+    pub original: Span,
+    pub duplicate: Span,
+}
+
+/// Metadata recorded for each ingested document
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocumentMetadata {
+    pub id: usize,
+    pub language: Option<DetectedLanguage>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -197,103 +298,56 @@ pub struct DedupStats {
     pub total_items: usize,
     pub unique_items: usize,
     pub duplicate_groups: usize,
+    #[serde(default)]
+    pub comparisons: usize, // Pairs scored
+    #[serde(default)]
+    pub pruned_comparisons: usize, // Pairs skipped by blocking
 }
 
+/// Thin state wrapper around the deduplication engine, managed by Tauri
 pub struct DedupManager {
-    texts: Vec<String>,
-    strategy: DedupStrategySettings,
-    classifier: TextClassifier,
+    engine: DeduplicationEngine,
 }
 
 impl DedupManager {
     pub fn new(strategy: DedupStrategySettings, _similarity_method: SimilarityMethod) -> Self {
         Self {
-            strategy: strategy.clone(),
-            texts: Vec::new(),
-            classifier: TextClassifier::new(strategy),
+            engine: DeduplicationEngine::new(strategy),
         }
     }
 
+    pub fn engine(&self) -> &DeduplicationEngine {
+        &self.engine
+    }
+
+    pub fn engine_mut(&mut self) -> &mut DeduplicationEngine {
+        &mut self.engine
+    }
+
     pub fn clear(&mut self) -> Result<()> {
-        self.texts.clear();
-        self.classifier.clear();
+        self.engine.clear();
         Ok(())
     }
 
     pub fn add_text(&mut self, text: String) -> usize {
-        let idx = self.classifier.add_text(text.clone());
-        self.texts.push(text);
-        idx
+        self.engine.ingest(text)
     }
 
     pub fn update_strategy(&mut self, strategy_json: &str) -> Result<()> {
         let strategy: DedupStrategySettings = serde_json::from_str(strategy_json)?;
-        self.strategy = strategy.clone();
-        self.classifier.update_strategy(strategy);
+        self.engine.update_strategy(strategy)?;
         Ok(())
     }
 
     pub fn get_strategy(&self) -> String {
-        serde_json::to_string(&self.strategy).unwrap_or_default()
+        serde_json::to_string(self.engine.get_strategy()).unwrap_or_default()
     }
 
     pub fn get_text(&self, id: usize) -> Option<String> {
-        self.classifier.get_text(id)
+        self.engine.get_text(id)
     }
 
-    pub fn deduplicate_texts(&mut self) -> Result<DedupResults, String> {
-        // Early return if no texts
-        if self.texts.is_empty() {
-            return Ok(DedupResults {
-                duplicate_groups: Vec::new(),
-                stats: DedupStats {
-                    total_items: 0,
-                    unique_items: 0,
-                    duplicate_groups: 0,
-                }
-            });
-        }
-
-        // Use TextClassifier to find duplicates
-        let mut duplicate_indices = self.classifier.find_duplicates();
-        
-        // Apply max_duplicate_count limit if specified
-        if let Some(max_count) = self.strategy.max_duplicate_count {
-            duplicate_indices.truncate(max_count);
-        }
-
-        // Convert indices to DuplicateGroups
-        let duplicate_groups: Vec<DuplicateGroup> = duplicate_indices
-            .into_iter()
-            .map(|indices| {
-                let texts: Vec<String> = indices
-                    .iter()
-                    .filter_map(|&idx| self.texts.get(idx).cloned())
-                    .collect();
-                
-                DuplicateGroup {
-                    original: texts[0].clone(),
-                    duplicates: texts[1..].to_vec(),
-                    similarity: 0.0, // TODO: Calculate similarity
-                }
-            })
-            .collect();
-
-        // Calculate stats
-        let total_items = self.texts.len();
-        let duplicate_groups_count = duplicate_groups.len();
-        let unique_items = total_items - duplicate_groups
-            .iter()
-            .map(|group| group.duplicates.len())
-            .sum::<usize>();
-
-        Ok(DedupResults {
-            duplicate_groups,
-            stats: DedupStats {
-                total_items,
-                unique_items,
-                duplicate_groups: duplicate_groups_count,
-            }
-        })
+    pub fn deduplicate_texts(&mut self) -> Result<DedupResults> {
+        Ok(self.engine.run()?)
     }
 }
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use crate::core::blocking::{BlockingRule, KeyExpression};
+use crate::core::logs::LogStrategy;
+use crate::core::normalization::NormalizationRule;
+use crate::core::normalizers::DomainNormalizer;
+use crate::core::records::{RecordStrategy, ScoreCombination};
+use crate::error::AppError;
+use crate::state::{DedupStrategySettings, FeatureExtraction, FuzzyAlgorithm, SimilarityMethod};
+
+/// A single problem found in a strategy, pointing at the offending field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub field: String,
+    pub message: String,
+    pub suggestion: Option<String>,
+}
+
+impl ValidationIssue {
+    fn new(field: &str, message: impl Into<String>, suggestion: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+            suggestion: Some(suggestion.into()),
+        }
+    }
+}
+
+impl DedupStrategySettings {
+    /// **Checks the strategy and returns every problem found (empty when valid).**
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        if !(0.0..=1.0).contains(&self.similarity_threshold) {
+            issues.push(ValidationIssue::new(
+                "similarity_threshold",
+                format!("similarity_threshold must be between 0.0 and 1.0, got {}", self.similarity_threshold),
+                format!("set similarity_threshold to {}", clamp_unit(self.similarity_threshold)),
+            ));
+        }
+
+        if self.ngram_size == Some(0) {
+            issues.push(ValidationIssue::new(
+                "ngram_size",
+                "ngram_size must be at least 1",
+                "set ngram_size to 3",
+            ));
+        }
+
+        match self.feature_extraction {
+            Some(FeatureExtraction::WordShingles(0)) | Some(FeatureExtraction::CharShingles(0)) => {
+                issues.push(ValidationIssue::new(
+                    "feature_extraction",
+                    "shingle size k must be at least 1",
+                    "use a shingle size of 2 or 3",
+                ));
+            }
+            _ => {}
+        }
+
+        if let SimilarityMethod::Fuzzy(FuzzyAlgorithm::NGram) = self.similarity_method {
+            if self.ngram_size.is_none() {
+                issues.push(ValidationIssue::new(
+                    "ngram_size",
+                    "Fuzzy(NGram) requires an n-gram size",
+                    "set ngram_size to 3",
+                ));
+            }
+        }
+
+        if self.winnowing_window == Some(0) {
+            issues.push(ValidationIssue::new(
+                "winnowing_window",
+                "winnowing_window must be at least 1",
+                "set winnowing_window to 4",
+            ));
+        }
+
+        if self.max_duplicate_count == Some(0) {
+            issues.push(ValidationIssue::new(
+                "max_duplicate_count",
+                "max_duplicate_count of 0 discards every duplicate group",
+                "leave max_duplicate_count empty or set it to 1000",
+            ));
+        }
+
+        if let Some(record) = &self.record {
+            validate_record(record, &mut issues);
+        }
+
+        if let Some(log) = &self.log {
+            validate_log(log, &mut issues);
+        }
+
+        for (idx, rule) in self.config.iter().flat_map(|config| &config.user_masking_rules).enumerate() {
+            let field = format!("config.user_masking_rules[{}]", idx);
+            if rule.name.trim().is_empty() {
+                issues.push(ValidationIssue::new(&format!("{}.name", field), "masking rules need a name", "name the rule"));
+            }
+            if let Err(message) = rule.compile() {
+                issues.push(ValidationIssue::new(
+                    &format!("{}.pattern", field),
+                    message,
+                    "fix the regular expression or remove the rule",
+                ));
+            }
+        }
+
+        for (idx, normalizer) in self.domain_normalizers.iter().flatten().enumerate() {
+            validate_domain_normalizer(format!("domain_normalizers[{}]", idx), normalizer, &mut issues);
+        }
+
+        for (idx, step) in self.normalization.iter().flatten().enumerate() {
+            if let NormalizationRule::Domain { normalizers } = &step.rule {
+                for (n, normalizer) in normalizers.iter().enumerate() {
+                    let path = format!("normalization[{}].rule.normalizers[{}]", idx, n);
+                    validate_domain_normalizer(path, normalizer, &mut issues);
+                }
+            }
+        }
+
+        for (idx, rule) in self.blocking.iter().flatten().enumerate() {
+            validate_blocking_rule(idx, rule, &mut issues);
+        }
+
+        if let Some(config) = &self.config {
+            if config.user_paragraph_delimiters.as_deref() == Some("") {
+                issues.push(ValidationIssue::new(
+                    "config.user_paragraph_delimiters",
+                    "paragraph delimiter must not be empty",
+                    "leave it unset to use \"\\n\\n\"",
+                ));
+            }
+
+            if let Some(c) = config.user_sentence_delimiters.iter().find(|c| c.is_whitespace()) {
+                issues.push(ValidationIssue::new(
+                    "config.user_sentence_delimiters",
+                    format!("whitespace {:?} cannot be a sentence delimiter", c),
+                    "remove whitespace characters from the sentence delimiters",
+                ));
+            }
+
+            let base_threshold = config.base.default_similarity_threshold;
+            if !(0.0..=1.0).contains(&base_threshold) {
+                issues.push(ValidationIssue::new(
+                    "config.base.default_similarity_threshold",
+                    format!("default_similarity_threshold must be between 0.0 and 1.0, got {}", base_threshold),
+                    format!("set default_similarity_threshold to {}", clamp_unit(base_threshold)),
+                ));
+            }
+
+            if config.base.default_ngram_size == 0 {
+                issues.push(ValidationIssue::new(
+                    "config.base.default_ngram_size",
+                    "default_ngram_size must be at least 1",
+                    "set default_ngram_size to 3",
+                ));
+            }
+        }
+
+        issues
+    }
+
+    /// **Validates the strategy, failing with the first issue if any were found.**
+    pub fn ensure_valid(&self) -> Result<(), AppError> {
+        let issues = self.validate();
+        match issues.first() {
+            None => Ok(()),
+            Some(first) => {
+                let summary = issues
+                    .iter()
+                    .map(|issue| format!("{}: {}", issue.field, issue.message))
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                Err(AppError::invalid_strategy(
+                    first.field.clone(),
+                    format!("Invalid strategy: {}", summary),
+                    first.suggestion.clone(),
+                ))
+            }
+        }
+    }
+}
+
+/// Checks the record mode settings, with field paths under `record`
+fn validate_record(record: &RecordStrategy, issues: &mut Vec<ValidationIssue>) {
+    if record.fields.is_empty() {
+        issues.push(ValidationIssue::new(
+            "record.fields",
+            "record mode needs at least one field rule",
+            "add a rule such as { \"field\": \"name\", \"method\": \"Exact\" }",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&record.threshold) {
+        issues.push(ValidationIssue::new(
+            "record.threshold",
+            format!("record threshold must be between 0.0 and 1.0, got {}", record.threshold),
+            format!("set record.threshold to {}", clamp_unit(record.threshold)),
+        ));
+    }
+
+    for (idx, rule) in record.fields.iter().enumerate() {
+        let path = |name: &str| format!("record.fields[{}].{}", idx, name);
+        if rule.weight < 0.0 || rule.weight.is_nan() {
+            issues.push(ValidationIssue {
+                field: path("weight"),
+                message: format!("weight of '{}' must not be negative", rule.field),
+                suggestion: Some("use a weight of 0 to ignore the field".to_string()),
+            });
+        }
+        if !matches!(rule.method, SimilarityMethod::Exact | SimilarityMethod::Levenshtein | SimilarityMethod::Fuzzy(_)) {
+            issues.push(ValidationIssue {
+                field: path("method"),
+                message: format!("field '{}' must use a string metric, got {:?}", rule.field, rule.method),
+                suggestion: Some("use Exact, Levenshtein or a Fuzzy algorithm".to_string()),
+            });
+        }
+        for (normalizer_idx, normalizer) in rule.normalization.domain_normalizers.iter().flatten().enumerate() {
+            validate_domain_normalizer(format!("{}[{}]", path("normalization.domain_normalizers"), normalizer_idx), normalizer, issues);
+        }
+        if record.combination == ScoreCombination::FellegiSunter {
+            for (name, p) in [("m_probability", rule.m_probability), ("u_probability", rule.u_probability)] {
+                if !(p > 0.0 && p < 1.0) {
+                    issues.push(ValidationIssue {
+                        field: path(name),
+                        message: format!("{} of '{}' must be strictly between 0 and 1, got {}", name, rule.field, p),
+                        suggestion: Some(format!("set {} to {}", name, if name == "m_probability" { 0.9 } else { 0.05 })),
+                    });
+                }
+            }
+        }
+    }
+
+    if record.combination == ScoreCombination::WeightedAverage
+        && !record.fields.is_empty()
+        && record.fields.iter().all(|rule| rule.weight <= 0.0)
+    {
+        issues.push(ValidationIssue::new(
+            "record.fields",
+            "at least one field needs a positive weight",
+            "give the most reliable field a weight of 1",
+        ));
+    }
+
+    if let Some(key) = &record.blocking_key {
+        if key.fields.is_empty() || key.prefix == Some(0) {
+            issues.push(ValidationIssue::new(
+                "record.blocking_key",
+                "blocking key needs at least one field and a prefix of at least 1",
+                "use a prefix of 3 on a field such as the last name",
+            ));
+        }
+    }
+}
+
+/// Checks the log mode settings, with field paths under `log`
+fn validate_log(log: &LogStrategy, issues: &mut Vec<ValidationIssue>) {
+    if log.depth < 3 {
+        issues.push(ValidationIssue::new(
+            "log.depth",
+            format!("log tree depth counts the root, length and leaf layers and must be at least 3, got {}", log.depth),
+            "use a depth of 4",
+        ));
+    }
+
+    if !(0.0..=1.0).contains(&log.similarity_threshold) {
+        issues.push(ValidationIssue::new(
+            "log.similarity_threshold",
+            format!("log similarity threshold must be between 0.0 and 1.0, got {}", log.similarity_threshold),
+            format!("set log.similarity_threshold to {}", clamp_unit(log.similarity_threshold)),
+        ));
+    }
+
+    if log.max_children == 0 {
+        issues.push(ValidationIssue::new(
+            "log.max_children",
+            "log tree nodes need room for at least 1 child",
+            "use 100 children per node",
+        ));
+    }
+}
+
+/// Checks the parameters of a domain normalizer found at `path`
+fn validate_domain_normalizer(path: String, normalizer: &DomainNormalizer, issues: &mut Vec<ValidationIssue>) {
+    if let DomainNormalizer::Phone { default_country_code: Some(code) } = normalizer {
+        if !(1..=999).contains(code) {
+            issues.push(ValidationIssue {
+                field: format!("{}.default_country_code", path),
+                message: format!("country calling codes have 1 to 3 digits, got {}", code),
+                suggestion: Some("use a code such as 1 or 44".to_string()),
+            });
+        }
+    }
+}
+
+/// Checks one blocking rule, with field path `blocking[idx]`
+fn validate_blocking_rule(idx: usize, rule: &BlockingRule, issues: &mut Vec<ValidationIssue>) {
+    let field = format!("blocking[{}]", idx);
+    let (message, suggestion) = match rule {
+        BlockingRule::Prefix { length: 0 } => ("prefix length must be at least 1".to_string(), "use a prefix of 3 characters"),
+        BlockingRule::Phonetic { words: 0 } => ("phonetic key needs at least 1 word".to_string(), "encode the first word"),
+        BlockingRule::SortedNeighbourhood { window } if *window < 2 => (
+            format!("sorted neighbourhood window must be at least 2, got {}", window),
+            "use a window of 10",
+        ),
+        BlockingRule::LengthBand { tolerance } if !(0.0..1.0).contains(tolerance) => (
+            format!("length band tolerance must be at least 0.0 and below 1.0, got {}", tolerance),
+            "use a tolerance of 0.3",
+        ),
+        BlockingRule::Key { expression } => match KeyExpression::parse(expression) {
+            Ok(_) => return,
+            Err(error) => (format!("invalid key expression: {}", error), "use a key such as (concat (prefix 3 (word 0)) (length-bucket 50))"),
+        },
+        _ => return,
+    };
+    issues.push(ValidationIssue::new(&field, message, suggestion));
+}
+
+/// Nearest value in 0..=1 (NaN maps to the default threshold)
+fn clamp_unit(value: f64) -> f64 {
+    if value.is_nan() {
+        DedupStrategySettings::default().similarity_threshold
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
@@ -10,7 +10,7 @@ export const FuzzyAlgorithmSchema = z.nativeEnum(FuzzyAlgorithm);
 
 
 export const SimilarityMethodSchema = z.object({
-  type: z.enum(["Exact", "Semantic", "Levenshtein", "Fuzzy"]),
+  type: z.enum(["Exact", "Semantic", "Levenshtein", "Fuzzy", "TfIdfCosine", "Bm25", "Winnowing", "CodeClone"]),
   algorithm: FuzzyAlgorithmSchema.optional()
 }).refine((data) => {
   // Ensure algorithm is present when type is Fuzzy
@@ -22,6 +22,108 @@ export const SimilarityMethodSchema = z.object({
   message: "FuzzyAlgorithm must be specified when type is Fuzzy"
 });
 
+/**
+ * Blocking rules; candidate pairs must pass every rule.
+ */
+export const BlockingRuleSchema = z.union([
+  z.object({ Prefix: z.object({ length: z.number() }) }),
+  z.object({ SortedNeighbourhood: z.object({ window: z.number() }) }),
+  z.object({ Phonetic: z.object({ words: z.number() }) }),
+  z.object({ LengthBand: z.object({ tolerance: z.number() }) }),
+  z.object({ Key: z.object({ expression: z.string() }) }),
+]);
+
+/**
+ * Built-in domain normalizers (URL, email, phone, date, number).
+ */
+export const DomainNormalizerSchema = z.union([
+  z.literal("Url"),
+  z.literal("Email"),
+  z.object({ Phone: z.object({ default_country_code: z.number().nullable() }) }),
+  z.object({ Date: z.object({ day_first: z.boolean() }) }),
+  z.literal("Number"),
+]);
+
+/**
+ * One step of the ordered normalization pipeline.
+ */
+export const NormalizationRuleSchema = z.union([
+  z.object({ Domain: z.object({ normalizers: z.array(DomainNormalizerSchema) }) }),
+  z.enum(["Case", "Whitespace", "Punctuation", "Stopwords", "Stemming", "Encoding"]),
+  z.object({ Unicode: z.object({ form: z.enum(["Nfc", "Nfd", "Nfkc", "Nfkd"]) }) }),
+]);
+
+export const PipelineStepSchema = z.object({
+  rule: NormalizationRuleSchema,
+  enabled: z.boolean().default(true),
+});
+
+/**
+ * Comparison features: the split units, or word/character shingles that keep their order.
+ * Shingles also set the k-gram unit of the Winnowing and CodeClone fingerprints.
+ */
+export const FeatureExtractionSchema = z.union([
+  z.literal("BagOfUnits"),
+  z.object({ WordShingles: z.number().min(1) }),
+  z.object({ CharShingles: z.number().min(1) }),
+]);
+
+/**
+ * Record mode: records are compared field by field. Field methods use the
+ * backend's serialized form ("Exact", "Levenshtein" or { Fuzzy: algorithm }).
+ */
+export const FieldRuleSchema = z.object({
+  field: z.string(),
+  method: z.union([z.enum(["Exact", "Levenshtein"]), z.object({ Fuzzy: FuzzyAlgorithmSchema })]),
+  normalization: z.object({
+    case_sensitive: z.boolean().nullable().optional(),
+    ignore_whitespace: z.boolean().nullable().optional(),
+    ignore_punctuation: z.boolean().nullable().optional(),
+    normalize_unicode: z.boolean().nullable().optional(),
+    ignore_stopwords: z.boolean().nullable().optional(),
+    stemming: z.boolean().nullable().optional(),
+    encoding_normalization: z.boolean().nullable().optional(),
+    domain_normalizers: z.array(DomainNormalizerSchema).nullable().optional(),
+  }).optional(),
+  ngram_size: z.number().nullable().optional(),
+  weight: z.number().min(0).default(1),
+  agreement_threshold: z.number().min(0).max(1).default(0.9),
+  m_probability: z.number().default(0.9), // Fellegi-Sunter only, strictly between 0 and 1
+  u_probability: z.number().default(0.05),
+});
+
+export const RecordStrategySchema = z.object({
+  fields: z.array(FieldRuleSchema).min(1),
+  combination: z.enum(["WeightedAverage", "FellegiSunter"]).default("WeightedAverage"),
+  threshold: z.number().min(0).max(1),
+  blocking_key: z.object({
+    fields: z.array(z.string()),
+    prefix: z.number().nullable().optional(),
+  }).nullable().optional(),
+});
+
+/**
+ * Log mode: Drain-style template mining over the lines of the texts.
+ */
+export const LogStrategySchema = z.object({
+  depth: z.number().min(3).default(4),
+  similarity_threshold: z.number().min(0).max(1).default(0.4),
+  max_children: z.number().min(1).default(100),
+  max_examples: z.number().default(3),
+  apply_masks: z.boolean().default(true),
+});
+
+/**
+ * Code mode: lexer settings for the CodeClone method (Type-2 clones with normalization).
+ */
+export const CodeStrategySchema = z.object({
+  language: z.enum([
+    "Generic", "C", "Cpp", "CSharp", "Java", "JavaScript", "TypeScript", "Rust", "Go", "Python", "Ruby", "Shell", "Sql",
+  ]).default("Generic"),
+  normalize_identifiers: z.boolean().default(false),
+  normalize_literals: z.boolean().default(false),
+});
+
 /**
  * Configuration for similarity weights used in deduplication.
  * The weights determine how different aspects of text comparison contribute to the overall similarity score.
@@ -95,6 +197,14 @@ export const DedupStrategySchema = z.object({
   ignore_stopwords: z.boolean(),
   stemming: z.boolean(),
   ngram_size: z.number(),
+  winnowing_window: z.number().optional(),
+  feature_extraction: FeatureExtractionSchema.nullable().optional(),
+  blocking: z.array(BlockingRuleSchema).nullable().optional(),
+  domain_normalizers: z.array(DomainNormalizerSchema).nullable().optional(),
+  normalization: z.array(PipelineStepSchema).nullable().optional(),
+  record: RecordStrategySchema.nullable().optional(),
+  log: LogStrategySchema.nullable().optional(),
+  code: CodeStrategySchema.nullable().optional(),
   language_detection: z.boolean(),
   max_duplicate_count: z.number().optional(),
   encoding_normalization: z.boolean(),
@@ -111,16 +221,39 @@ export const DedupPresetsSchema = z.object({
 //
 // 3) Zod schema for DuplicateGroup
 //
+export const SpanSchema = z.object({
+  start: z.number(),
+  end: z.number(),
+  char_start: z.number(),
+  char_end: z.number(),
+});
+
+export const SpanMatchSchema = z.object({
+  original: SpanSchema,
+  duplicate: SpanSchema,
+});
+
+export const DuplicateMemberSchema = z.object({
+  id: z.number(),
+  similarity: z.number(),
+  span: SpanSchema,
+  matches: z.array(SpanMatchSchema),
+});
+
 export const DuplicateGroupSchema = z.object({
   original: z.string(),
   duplicates: z.array(z.string()),
   similarity: z.number(),
+  original_id: z.number().optional(),
+  members: z.array(DuplicateMemberSchema).optional(),
 });
 
 export const DuplicateStatsSchema = z.object({
   duplicate_groups: z.number(),
   total_items: z.number(),
   unique_items: z.number(),
+  comparisons: z.number().optional(),
+  pruned_comparisons: z.number().optional(),
 });
 
 export const DuplicateResultSchema = z.object({